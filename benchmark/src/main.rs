@@ -0,0 +1,177 @@
+// Benchmark: run every registered solver on every instance class
+// and size over several seeds, then summarize the times and calls.
+//
+// Each run happens in a child process (this program started with
+// --run), so a run that exceeds the time limit can simply be killed.
+// Once a solver hits the limit, it is skipped for the remaining seeds
+// of that size and for all larger sizes of the class.
+
+mod report;
+mod stats;
+
+use knapsack::harness::timed;
+use knapsack::instances::{make_instance, InstanceClass};
+use knapsack::item::{copy_items, sum_weights};
+use knapsack::solvers::{find_solver, solvers};
+use knapsack::verify::verify;
+use stats::{summarize, Run};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use utils::Xoshiro256;
+
+const SIZES: [i32; 4] = [10, 20, 40, 80];
+const NUM_SEEDS: u64 = 5;
+const RANGE: i32 = 100;
+const TIME_LIMIT: Duration = Duration::from_secs(1);
+
+const CSV_FILE: &str = "benchmark.csv";
+const MARKDOWN_FILE: &str = "benchmark.md";
+
+// Run one solver on one instance and print "calls nanoseconds".
+fn run_one(solver_name: &str, class: InstanceClass, num_items: i32, seed: u64) {
+    let solver = find_solver(solver_name).expect("Unknown solver");
+    let mut prng = Xoshiro256::new_with_seed(seed);
+    let items = make_instance(&mut prng, class, num_items, RANGE);
+    let allowed_weight = sum_weights(&items, true) / 2;
+
+    // Verify against the untouched instance, not the solver's copy.
+    let mut test_items = copy_items(&items);
+    let ((solution, value, calls), elapsed) =
+        timed(|| (solver.solve)(&mut test_items, allowed_weight));
+    if let Err(violation) = verify(&items, allowed_weight, &solution, value) {
+        panic!(
+            "{} returned an invalid solution: {}",
+            solver_name, violation
+        );
+    }
+    println!("{} {}", calls, elapsed.as_nanos());
+}
+
+// How a run in a child process ended.
+#[derive(Clone, Copy)]
+enum Outcome {
+    Finished(i64, Duration),
+    TimedOut,
+    // The child panicked, for example on an invalid solution,
+    // or printed something other than "calls nanoseconds".
+    Failed,
+}
+
+// Run one solver on one instance in a child process.
+fn run_child(
+    solver_name: &str,
+    class: InstanceClass,
+    num_items: i32,
+    seed: u64,
+) -> io::Result<Outcome> {
+    let mut child = Command::new(env::current_exe()?)
+        .args([
+            "--run",
+            solver_name,
+            class.name(),
+            &num_items.to_string(),
+            &seed.to_string(),
+        ])
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let start = Instant::now();
+    while child.try_wait()?.is_none() {
+        if start.elapsed() > TIME_LIMIT {
+            child.kill()?;
+            child.wait()?;
+            return Ok(Outcome::TimedOut);
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    // The child's stderr isn't captured, so its panic message
+    // is already on the screen.
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Ok(Outcome::Failed);
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = text.split_whitespace().collect();
+    if fields.len() != 2 {
+        return Ok(Outcome::Failed);
+    }
+    match (fields[0].parse(), fields[1].parse()) {
+        (Ok(calls), Ok(nanos)) => Ok(Outcome::Finished(calls, Duration::from_nanos(nanos))),
+        _ => Ok(Outcome::Failed),
+    }
+}
+
+fn run_benchmark() -> io::Result<Vec<Run>> {
+    let mut runs = vec![];
+    for solver in solvers() {
+        for class in InstanceClass::all() {
+            'sizes: for num_items in SIZES {
+                for seed in 1..=NUM_SEEDS {
+                    let outcome = run_child(solver.name, class, num_items, seed)?;
+                    let finished = match outcome {
+                        Outcome::Finished(calls, elapsed) => Some((calls, elapsed)),
+                        _ => None,
+                    };
+                    runs.push(Run {
+                        solver: solver.name.to_string(),
+                        class: class.name().to_string(),
+                        num_items,
+                        calls: finished.map(|(calls, _)| calls),
+                        elapsed: finished.map(|(_, elapsed)| elapsed),
+                        failed: matches!(outcome, Outcome::Failed),
+                    });
+                    match outcome {
+                        Outcome::Finished(..) => {}
+                        Outcome::TimedOut => {
+                            println!(
+                                "{} hit the time limit on {} with {} items",
+                                solver.name, class, num_items
+                            );
+                            break 'sizes;
+                        }
+                        Outcome::Failed => println!(
+                            "{} failed on {} with {} items and seed {}",
+                            solver.name, class, num_items, seed
+                        ),
+                    }
+                }
+            }
+        }
+    }
+    Ok(runs)
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() == 6 && args[1] == "--run" {
+        let class = args[3].parse().expect("Unknown instance class");
+        let num_items = args[4].parse().expect("Error parsing item count");
+        let seed = args[5].parse().expect("Error parsing seed");
+        run_one(&args[2], class, num_items, seed);
+        return Ok(());
+    }
+
+    println!("*** Benchmark ***");
+    println!("Sizes:      {:?}", SIZES);
+    println!("Seeds:      {}", NUM_SEEDS);
+    println!("Range:      {}", RANGE);
+    println!("Time limit: {:?}", TIME_LIMIT);
+    println!();
+
+    let summaries = summarize(&run_benchmark()?);
+    report::write_csv(&mut BufWriter::new(File::create(CSV_FILE)?), &summaries)?;
+    report::write_markdown(
+        &mut BufWriter::new(File::create(MARKDOWN_FILE)?),
+        &summaries,
+    )?;
+    println!();
+    report::write_markdown(&mut io::stdout(), &summaries)?;
+    println!();
+    println!("Wrote {} and {}", CSV_FILE, MARKDOWN_FILE);
+    Ok(())
+}
@@ -0,0 +1,109 @@
+// Write the summaries as CSV and as a Markdown table.
+
+use crate::stats::Summary;
+use std::io::{self, Write};
+use std::time::Duration;
+
+const HEADERS: [&str; 10] = [
+    "solver",
+    "class",
+    "items",
+    "runs",
+    "timeouts",
+    "failures",
+    "median_ms",
+    "p90_ms",
+    "max_ms",
+    "median_calls",
+];
+
+fn format_millis(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!("{:.3}", duration.as_secs_f64() * 1000.0),
+        None => "-".to_string(),
+    }
+}
+
+fn fields(summary: &Summary) -> Vec<String> {
+    vec![
+        summary.solver.clone(),
+        summary.class.clone(),
+        summary.num_items.to_string(),
+        summary.runs.to_string(),
+        summary.timeouts.to_string(),
+        summary.failures.to_string(),
+        format_millis(summary.median),
+        format_millis(summary.p90),
+        format_millis(summary.max),
+        summary
+            .median_calls
+            .map_or("-".to_string(), |calls| calls.to_string()),
+    ]
+}
+
+pub fn write_csv(out: &mut impl Write, summaries: &[Summary]) -> io::Result<()> {
+    writeln!(out, "{}", HEADERS.join(","))?;
+    for summary in summaries {
+        writeln!(out, "{}", fields(summary).join(","))?;
+    }
+    Ok(())
+}
+
+pub fn write_markdown(out: &mut impl Write, summaries: &[Summary]) -> io::Result<()> {
+    writeln!(out, "| {} |", HEADERS.join(" | "))?;
+    // Left-align the names, right-align the numbers.
+    let alignments: Vec<&str> = HEADERS
+        .iter()
+        .enumerate()
+        .map(|(i, _)| if i < 2 { ":---" } else { "---:" })
+        .collect();
+    writeln!(out, "| {} |", alignments.join(" | "))?;
+    for summary in summaries {
+        writeln!(out, "| {} |", fields(summary).join(" | "))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_summary() -> Summary {
+        Summary {
+            solver: "greedy".to_string(),
+            class: "subset-sum".to_string(),
+            num_items: 20,
+            runs: 5,
+            timeouts: 1,
+            failures: 0,
+            median: Some(Duration::from_micros(1500)),
+            p90: Some(Duration::from_millis(2)),
+            max: None,
+            median_calls: Some(20),
+        }
+    }
+
+    #[test]
+    fn csv_has_a_header_and_a_row_per_summary() {
+        let mut out = vec![];
+        write_csv(&mut out, &[make_summary()]).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], HEADERS.join(","));
+        assert_eq!(lines[1], "greedy,subset-sum,20,5,1,0,1.500,2.000,-,20");
+    }
+
+    #[test]
+    fn markdown_is_a_table() {
+        let mut out = vec![];
+        write_markdown(&mut out, &[make_summary()]).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("| :--- | :--- | ---: |"));
+        assert_eq!(
+            lines[2],
+            "| greedy | subset-sum | 20 | 5 | 1 | 0 | 1.500 | 2.000 | - | 20 |"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// Summaries of the runs of one solver on one class and size.
+
+use std::time::Duration;
+
+// The result of one run. calls and elapsed are None if the run hit
+// the time limit or failed.
+pub struct Run {
+    pub solver: String,
+    pub class: String,
+    pub num_items: i32,
+    pub calls: Option<i64>,
+    pub elapsed: Option<Duration>,
+    // The run panicked or returned an invalid solution.
+    pub failed: bool,
+}
+
+pub struct Summary {
+    pub solver: String,
+    pub class: String,
+    pub num_items: i32,
+    pub runs: usize,
+    pub timeouts: usize,
+    pub failures: usize,
+    // These are None if every run timed out or failed.
+    pub median: Option<Duration>,
+    pub p90: Option<Duration>,
+    pub max: Option<Duration>,
+    pub median_calls: Option<i64>,
+}
+
+// Return the p-th percentile of the sorted values (nearest rank).
+pub fn percentile<T: Copy>(sorted: &[T], p: f64) -> Option<T> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+// Group the runs by solver, class and size and summarize each group.
+// Groups keep the order in which their first run appears.
+pub fn summarize(runs: &[Run]) -> Vec<Summary> {
+    let mut summaries: Vec<Summary> = vec![];
+    let mut groups: Vec<Vec<&Run>> = vec![];
+    for run in runs {
+        let position = summaries.iter().position(|summary| {
+            summary.solver == run.solver
+                && summary.class == run.class
+                && summary.num_items == run.num_items
+        });
+        match position {
+            Some(k) => groups[k].push(run),
+            None => {
+                summaries.push(Summary {
+                    solver: run.solver.clone(),
+                    class: run.class.clone(),
+                    num_items: run.num_items,
+                    runs: 0,
+                    timeouts: 0,
+                    failures: 0,
+                    median: None,
+                    p90: None,
+                    max: None,
+                    median_calls: None,
+                });
+                groups.push(vec![run]);
+            }
+        }
+    }
+
+    for (summary, group) in summaries.iter_mut().zip(groups) {
+        let mut times: Vec<Duration> = group.iter().filter_map(|run| run.elapsed).collect();
+        let mut calls: Vec<i64> = group.iter().filter_map(|run| run.calls).collect();
+        times.sort_unstable();
+        calls.sort_unstable();
+
+        summary.runs = group.len();
+        summary.failures = group.iter().filter(|run| run.failed).count();
+        summary.timeouts = group.len() - times.len() - summary.failures;
+        summary.median = percentile(&times, 50.0);
+        summary.p90 = percentile(&times, 90.0);
+        summary.max = times.last().copied();
+        summary.median_calls = percentile(&calls, 50.0);
+    }
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_run(solver: &str, num_items: i32, millis: Option<u64>) -> Run {
+        Run {
+            solver: solver.to_string(),
+            class: "uncorrelated".to_string(),
+            num_items,
+            calls: millis.map(|m| m as i64 * 100),
+            elapsed: millis.map(Duration::from_millis),
+            failed: false,
+        }
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let values: Vec<i32> = (1..=10).collect();
+        assert_eq!(percentile(&values, 50.0), Some(5));
+        assert_eq!(percentile(&values, 90.0), Some(9));
+        assert_eq!(percentile(&values, 100.0), Some(10));
+        assert_eq!(percentile(&values, 0.0), Some(1));
+        assert_eq!(percentile::<i32>(&[], 50.0), None);
+    }
+
+    #[test]
+    fn runs_are_grouped_and_timeouts_counted() {
+        let runs = vec![
+            make_run("greedy", 10, Some(3)),
+            make_run("exhaustive", 10, Some(7)),
+            make_run("greedy", 10, Some(1)),
+            make_run("greedy", 10, Some(2)),
+            make_run("exhaustive", 10, None),
+            Run {
+                failed: true,
+                ..make_run("exhaustive", 10, None)
+            },
+        ];
+        let summaries = summarize(&runs);
+        assert_eq!(summaries.len(), 2);
+
+        assert_eq!(summaries[0].solver, "greedy");
+        assert_eq!(summaries[0].runs, 3);
+        assert_eq!(summaries[0].median, Some(Duration::from_millis(2)));
+        assert_eq!(summaries[0].max, Some(Duration::from_millis(3)));
+        assert_eq!(summaries[0].median_calls, Some(200));
+
+        assert_eq!(summaries[1].timeouts, 1);
+        assert_eq!(summaries[1].failures, 1);
+        assert_eq!(summaries[1].median, Some(Duration::from_millis(7)));
+    }
+}
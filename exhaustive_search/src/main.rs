@@ -1,206 +1,145 @@
 // 1. Exhaustive search
 
-use std::time::Instant;
 //use std::time::{SystemTime, UNIX_EPOCH};
+use knapsack::exhaustive::exhaustive_search_limited;
+use knapsack::harness::{run_limited, timed};
+use knapsack::interactive::{interactive, is_interactive};
+use knapsack::item::copy_items;
+use knapsack::item::{make_items, sum_values, sum_weights, Parameters};
+use knapsack::limits::Limits;
+use knapsack::memo::{memoized_search_with, MemoKind};
+use knapsack::output::{output_format, record_limited, write_records, USAGE};
+use knapsack::preprocess::{preprocess, with_preprocessing_limited};
+use knapsack::verify::verify;
+use std::io;
+use std::process;
+use std::time::Duration;
 use utils::Prng;
 
 const NUM_ITEMS: i32 = 20; // A reasonable value for exhaustive search.
 
+// Stop a search after this long and report the best solution so far.
+const TIME_LIMIT: Duration = Duration::from_secs(10);
+
+// The memo stores to compare. The LRU cache is smaller than the
+// number of subproblems, so it has to recompute some of them.
+const MEMO_KINDS: [MemoKind; 3] = [MemoKind::Dense, MemoKind::Hash, MemoKind::Lru(500)];
+
 const MIN_VALUE: i32 = 1;
 const MAX_VALUE: i32 = 10;
 const MIN_WEIGHT: i32 = 4;
 const MAX_WEIGHT: i32 = 10;
 
-struct Item {
-    value: i32,
-    weight: i32,
-    is_selected: bool,
-}
-
-// Make some random items.
-fn make_items(
-    prng: &mut Prng,
-    num_items: i32,
-    min_value: i32,
-    max_value: i32,
-    min_weight: i32,
-    max_weight: i32,
-) -> Vec<Item> {
-    let mut items: Vec<Item> = Vec::with_capacity(num_items as usize);
-    for _ in 0..num_items {
-        let item = Item {
-            value: prng.next_i32(min_value, max_value),
-            weight: prng.next_i32(min_weight, max_weight),
-            is_selected: false,
-        };
-        items.push(item);
-    }
-    return items;
-}
-
-// Return a copy of the items.
-fn copy_items(items: &mut Vec<Item>) -> Vec<Item> {
-    let mut new_items: Vec<Item> = Vec::with_capacity(items.len());
-    for item in items {
-        let new_item = Item {
-            value: item.value,
-            weight: item.weight,
-            is_selected: item.is_selected,
-        };
-        new_items.push(new_item);
-    }
-    return new_items;
-}
-
-// Return the total value of the items.
-// If add_all is true, add up all items.
-// If add_all is false, only add up the selected items.
-fn sum_values(items: &mut Vec<Item>, add_all: bool) -> i32 {
-    if add_all {
-        return items.iter().map(|item| item.value).sum();
-    } else {
-        return items
-            .iter()
-            .filter(|item| item.is_selected)
-            .map(|item| item.value)
-            .sum();
-    }
-}
-
-// Return the total weight of the items.
-// If add_all is false, only add up the selected items.
-// If add_all is true, add up all items.
-fn sum_weights(items: &mut Vec<Item>, add_all: bool) -> i32 {
-    if add_all {
-        return items.iter().map(|item| item.weight).sum();
-    } else {
-        return items
-            .iter()
-            .filter(|item| item.is_selected)
-            .map(|item| item.weight)
-            .sum();
-    }
-}
-
-// Return the value of this solution.
-// If the solution is too heavy, return -1 so we prefer an empty solution.
-fn solution_value(items: &mut Vec<Item>, allowed_weight: i32) -> i32 {
-    // If the solution's total weight > allowed_weight,
-    // return -1 so even an empty solution is better.
-    if sum_weights(items, false) > allowed_weight {
-        return -1;
-    }
-
-    // Return the sum of the selected values.
-    return sum_values(items, false);
-}
-
-// Print the selected items.
-fn print_selected(items: &mut Vec<Item>) {
-    let mut num_printed = 0;
-    for i in 0..items.len() {
-        if items[i].is_selected {
-            print!("{}({}, {}) ", i, items[i].value, items[i].weight)
-        }
-        num_printed += 1;
-        if num_printed > 100 {
-            println!("...");
-            return;
-        }
-    }
-    println!();
-}
-
-// Run the algorithm. Display the elapsed time and solution.
-fn run_algorithm(
-    alg: &dyn Fn(&mut Vec<Item>, i32) -> (Vec<Item>, i32, i32),
-    items: &mut Vec<Item>,
-    allowed_weight: i32,
-) {
-    // Copy the items so the run isn't influenced by a previous run.
-    let mut test_items = copy_items(items);
-
-    let start = Instant::now();
-
-    // Run the algorithm.
-    let mut solution: Vec<Item>;
-    let total_value: i32;
-    let function_calls: i32;
-    (solution, total_value, function_calls) = alg(&mut test_items, allowed_weight);
-
-    let duration = start.elapsed();
-    println!("Elapsed: {:?}", duration);
-
-    print_selected(&mut solution);
-    println!(
-        "Value: {}, Weight: {}, Calls: {}",
-        total_value,
-        sum_weights(&mut solution, false),
-        function_calls
-    );
-    println!();
-}
-
-// Recursively assign values in or out of the solution.
-// Return the best assignment, value of that assignment,
-// and the number of function calls we made.
-fn exhaustive_search(items: &mut Vec<Item>, allowed_weight: i32) -> (Vec<Item>, i32, i32) {
-    return do_exhaustive_search(items, allowed_weight, 0);
-}
-
-fn do_exhaustive_search(
-    items: &mut Vec<Item>,
-    allowed_weight: i32,
-    next_index: i32,
-) -> (Vec<Item>, i32, i32) {
-    if next_index >= items.len() as i32 {
-        return (copy_items(items), solution_value(items, allowed_weight), 1);
+fn main() {
+    let parameters = Parameters {
+        num_items: NUM_ITEMS,
+        min_value: MIN_VALUE,
+        max_value: MAX_VALUE,
+        min_weight: MIN_WEIGHT,
+        max_weight: MAX_WEIGHT,
+        seed: 1337,
+    };
+
+    // With --interactive, ask for the parameters instead.
+    if is_interactive() {
+        interactive(parameters, "exhaustive");
+        return;
     }
 
-    let mut new_items_2 = copy_items(items);
-
-    // Skip next item:
-    let (solution_1, value_1, calls_1) =
-        do_exhaustive_search(items, allowed_weight, next_index + 1);
-
-    // Select next item:
-    new_items_2[next_index as usize].is_selected = true;
-    let (solution_2, value_2, calls_2) =
-        do_exhaustive_search(&mut new_items_2, allowed_weight, next_index + 1);
-
-    // Return the best solution.
-    if value_1 > value_2 {
-        return (solution_1, value_1, calls_1 + calls_2 + 1);
-    } else {
-        return (solution_2, value_2, calls_1 + calls_2 + 1);
+    // With --format json or csv, only print the results in that format.
+    let format = output_format().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        eprintln!("Usage: {} {}", env!("CARGO_PKG_NAME"), USAGE);
+        process::exit(2);
+    });
+    if let Some(format) = format {
+        let limits = Limits::none().with_time(TIME_LIMIT);
+        let records = vec![
+            record_limited(
+                "exhaustive",
+                &exhaustive_search_limited,
+                parameters,
+                &limits,
+            ),
+            record_limited(
+                "exhaustive-preprocessed",
+                &with_preprocessing_limited(&exhaustive_search_limited),
+                parameters,
+                &limits,
+            ),
+        ];
+        write_records(&mut io::stdout(), format, &records).expect("Error writing the results");
+        return;
     }
-}
 
-fn main() {
     // Prepare a Prng using the same seed each time.
     let mut prng = Prng::new_with_seed(1337);
     //prng.randomize();
 
     // Make some random items.
-    let mut items = make_items(
+    let items = make_items(
         &mut prng, NUM_ITEMS, MIN_VALUE, MAX_VALUE, MIN_WEIGHT, MAX_WEIGHT,
     );
-    let allowed_weight = sum_weights(&mut items, true) / 2;
+    let allowed_weight = sum_weights(&items, true) / 2;
 
     // Display basic parameters.
     println!("*** Parameters ***");
     println!("# items:        {}", NUM_ITEMS);
-    println!("Total value:    {}", sum_values(&mut items, true));
-    println!("Total weight:   {}", sum_weights(&mut items, true));
+    println!("Total value:    {}", sum_values(&items, true));
+    println!("Total weight:   {}", sum_weights(&items, true));
     println!("Allowed weight: {}", allowed_weight);
     println!();
 
+    let limits = Limits::none().with_time(TIME_LIMIT);
+
     // Exhaustive search
-    if NUM_ITEMS > 23 {
-        // Only run exhaustive search if num_items is small enough.
-        println!("Too many items for exhaustive search\n");
-    } else {
-        println!("*** Exhaustive Search ***");
-        run_algorithm(&exhaustive_search, &mut items, allowed_weight);
+    println!("*** Exhaustive Search ***");
+    run_limited(&exhaustive_search_limited, &items, allowed_weight, &limits);
+
+    // Preprocessing shrinks the instance before the search.
+    let report = preprocess(&items, allowed_weight).report;
+    println!("*** Preprocessing ***");
+    println!("Too heavy:      {}", report.too_heavy);
+    println!("Dominated:      {}", report.dominated);
+    println!("Fixed in:       {}", report.fixed_in);
+    println!("Fixed out:      {}", report.fixed_out);
+    println!();
+
+    println!("*** Exhaustive Search with Preprocessing ***");
+    run_limited(
+        &with_preprocessing_limited(&exhaustive_search_limited),
+        &items,
+        allowed_weight,
+        &limits,
+    );
+
+    // The memo solves every subproblem once instead of
+    // exploring the same subtrees again and again.
+    println!("*** Memoized Search ***");
+    println!(
+        "{:<10} {:>6} {:>8} {:>8} {:>8} {:>8} {:>9} {:>10} {:>12}",
+        "Store", "Value", "Calls", "Hits", "Misses", "Entries", "Evictions", "Memory", "Elapsed"
+    );
+    for kind in MEMO_KINDS {
+        let mut test_items = copy_items(&items);
+        let (((solution, value, calls), stats), duration) =
+            timed(|| memoized_search_with(&mut test_items, allowed_weight, kind));
+        // The LRU store evicts and recomputes, so check every store.
+        if let Err(violation) = verify(&items, allowed_weight, &solution, value) {
+            println!("INVALID SOLUTION for store {}: {}", kind, violation);
+        }
+        println!(
+            "{:<10} {:>6} {:>8} {:>8} {:>8} {:>8} {:>9} {:>10} {:>12}",
+            kind.to_string(),
+            value,
+            calls,
+            stats.hits,
+            stats.misses,
+            stats.entries,
+            stats.evictions,
+            format!("{} B", stats.memory),
+            format!("{:?}", duration)
+        );
     }
+    println!();
 }
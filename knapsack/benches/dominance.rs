@@ -0,0 +1,58 @@
+// Compare the pairwise dominance computation with the sweeps.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use knapsack::dominance::{block_counts, init_block_lists, sweep_block_lists};
+use knapsack::item::{make_items, Item};
+use std::hint::black_box;
+use utils::{Generator, Prng};
+
+const MIN_VALUE: i32 = 1;
+const MAX_VALUE: i32 = 1000;
+const MIN_WEIGHT: i32 = 1;
+const MAX_WEIGHT: i32 = 1000;
+
+// Make random items. If correlated, heavier items are more valuable
+// so that few items can block each other and the lists are short.
+fn make_bench_items(num_items: i32, correlated: bool) -> Vec<Item> {
+    let mut prng = Prng::new_with_seed(1337);
+    let mut items = make_items(
+        &mut prng, num_items, MIN_VALUE, MAX_VALUE, MIN_WEIGHT, MAX_WEIGHT,
+    );
+    if correlated {
+        for item in items.iter_mut() {
+            item.value = item.weight + prng.next_i32(0, 10);
+        }
+    }
+    items
+}
+
+fn bench_dominance(c: &mut Criterion, name: &str, correlated: bool) {
+    let mut group = c.benchmark_group(name);
+    for num_items in [100, 1000, 4000] {
+        let items = make_bench_items(num_items, correlated);
+
+        group.bench_with_input(
+            BenchmarkId::new("init_block_lists", num_items),
+            &items,
+            |b, items| b.iter(|| init_block_lists(black_box(items))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("sweep_block_lists", num_items),
+            &items,
+            |b, items| b.iter(|| sweep_block_lists(black_box(items))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("block_counts", num_items),
+            &items,
+            |b, items| b.iter(|| block_counts(black_box(items))),
+        );
+    }
+    group.finish();
+}
+
+fn dominance(c: &mut Criterion) {
+    bench_dominance(c, "dominance/uncorrelated", false);
+    bench_dominance(c, "dominance/correlated", true);
+}
+
+criterion_group!(benches, dominance);
+criterion_main!(benches);
@@ -0,0 +1,108 @@
+// Micro-benchmarks for the hot pieces the solvers are built from.
+// The items are the ones the programs make with seed 1337.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use knapsack::dominance::init_block_lists;
+use knapsack::dp::{fill_table, item_weights, Multiplicity};
+use knapsack::item::{copy_items, make_items, sum_weights, Item};
+use std::hint::black_box;
+use utils::{GeneratorKind, Prng};
+
+const MIN_VALUE: i32 = 1;
+const MAX_VALUE: i32 = 10;
+const MIN_WEIGHT: i32 = 4;
+const MAX_WEIGHT: i32 = 10;
+
+const SIZES: [i32; 3] = [20, 100, 1000];
+
+// The number of values drawn per iteration of the generator benchmarks.
+const DRAWS: u64 = 1000;
+
+fn make_bench_items(num_items: i32) -> Vec<Item> {
+    let mut prng = Prng::new_with_seed(1337);
+    make_items(
+        &mut prng, num_items, MIN_VALUE, MAX_VALUE, MIN_WEIGHT, MAX_WEIGHT,
+    )
+}
+
+fn items(c: &mut Criterion) {
+    let mut group = c.benchmark_group("items");
+    for num_items in SIZES {
+        let items = make_bench_items(num_items);
+        group.throughput(Throughput::Elements(num_items as u64));
+        group.bench_with_input(
+            BenchmarkId::new("copy_items", num_items),
+            &items,
+            |b, items| b.iter(|| copy_items(black_box(items))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("sum_weights", num_items),
+            &items,
+            |b, items| b.iter(|| sum_weights(black_box(items), true)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("init_block_lists", num_items),
+            &items,
+            |b, items| b.iter(|| init_block_lists(black_box(items))),
+        );
+    }
+    group.finish();
+}
+
+// Fill the 0/1 knapsack table, which is all the inner loop of
+// dynamic_programming does, without the reconstruction.
+fn dp_inner_loop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dp");
+    for num_items in SIZES {
+        let items = make_bench_items(num_items);
+        let weights = item_weights(&items);
+        let capacity = (sum_weights(&items, true) / 2) as usize;
+        group.throughput(Throughput::Elements(
+            ((weights.len() + 1) * (capacity + 1)) as u64,
+        ));
+        group.bench_with_input(
+            BenchmarkId::new("fill_table", num_items),
+            &items,
+            |b, items| {
+                b.iter(|| {
+                    fill_table(
+                        black_box(&weights),
+                        capacity,
+                        Multiplicity::ZeroOne,
+                        |_| 0,
+                        |skip, take, i| match take {
+                            Some(value) => skip.max(value + items[i].value),
+                            None => skip,
+                        },
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn generators(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generators");
+    group.throughput(Throughput::Elements(DRAWS));
+    for kind in GeneratorKind::all() {
+        let mut generator = kind.make(1337);
+        group.bench_function(BenchmarkId::new("next_u64", kind), |b| {
+            b.iter(|| {
+                for _ in 0..DRAWS {
+                    black_box(generator.next_u64());
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("next_i32", kind), |b| {
+            b.iter(|| {
+                for _ in 0..DRAWS {
+                    black_box(generator.next_i32(MIN_VALUE, MAX_VALUE));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, items, dp_inner_loop, generators);
+criterion_main!(benches);
@@ -0,0 +1,376 @@
+// Simulated annealing for instances too large for the exact solvers.
+//
+// The search starts from the greedy solution and makes random moves:
+// flip one item in or out, or swap a selected item with one that isn't.
+// A move that makes the selection better is always accepted, a move
+// that makes it worse by delta is accepted with probability
+// exp(-delta / temperature). The temperature falls over the iterations,
+// so the search wanders at first and settles down later.
+//
+// A move can make the selection overweight. With a penalty, such a
+// selection scores its value minus the penalty per unit of excess
+// weight, so the search can pass through it. With repair, the worst
+// ratio items are dropped at once until the selection fits.
+//
+// Annealing proves nothing, so the result reports the LP dual bound
+// as its upper bound and the gap to it.
+
+use crate::greedy::{greedy, ratio_order, repair_recording};
+use crate::item::{copy_items, sum_weights, Item, Solution};
+use crate::limits::SearchResult;
+use crate::verify::lp_dual;
+use utils::{Generator, Prng};
+
+// How the temperature falls from start to end. The temperatures are
+// multiples of the mean item value, so they fit every instance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Schedule {
+    Geometric { start: f64, end: f64 },
+    Linear { start: f64, end: f64 },
+}
+
+impl Schedule {
+    // Return the temperature after the fraction progress of the iterations.
+    pub fn temperature(&self, progress: f64) -> f64 {
+        match *self {
+            Schedule::Geometric { start, end } => start * (end / start).powf(progress),
+            Schedule::Linear { start, end } => start + (end - start) * progress,
+        }
+    }
+}
+
+// What to do with overweight selections.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Infeasible {
+    // Subtract this multiple of the best value/weight ratio
+    // for every unit of weight above the allowed weight.
+    Penalty(f64),
+    // Drop the worst ratio items until the selection fits.
+    Repair,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnealingConfig {
+    pub iterations: i32,
+    pub schedule: Schedule,
+    // The probability that a move is a swap rather than a flip.
+    pub swap_probability: f64,
+    pub infeasible: Infeasible,
+    pub seed: u32,
+}
+
+impl Default for AnnealingConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 100_000,
+            schedule: Schedule::Geometric {
+                start: 1.0,
+                end: 0.001,
+            },
+            swap_probability: 0.5,
+            infeasible: Infeasible::Penalty(2.0),
+            seed: 1337,
+        }
+    }
+}
+
+impl AnnealingConfig {
+    pub fn with_iterations(self, iterations: i32) -> Self {
+        Self { iterations, ..self }
+    }
+
+    pub fn with_schedule(self, schedule: Schedule) -> Self {
+        Self { schedule, ..self }
+    }
+
+    pub fn with_swap_probability(self, swap_probability: f64) -> Self {
+        Self {
+            swap_probability,
+            ..self
+        }
+    }
+
+    pub fn with_infeasible(self, infeasible: Infeasible) -> Self {
+        Self { infeasible, ..self }
+    }
+
+    pub fn with_seed(self, seed: u32) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+// The state of the search: a selection with its value and weight.
+struct State {
+    selected: Vec<bool>,
+    value: i32,
+    weight: i32,
+}
+
+impl State {
+    fn flip(&mut self, items: &[Item], i: usize) {
+        let sign = if self.selected[i] { -1 } else { 1 };
+        self.selected[i] = !self.selected[i];
+        self.value += sign * items[i].value;
+        self.weight += sign * items[i].weight;
+    }
+}
+
+// The instance, the current state and what it takes to move it.
+struct Annealer<'a> {
+    items: &'a [Item],
+    allowed_weight: i32,
+    order: Vec<usize>,
+    swap_probability: f64,
+    infeasible: Infeasible,
+    // The penalty per unit of excess weight, 0 with repair.
+    penalty: f64,
+    state: State,
+    // The items the last repair dropped, so a move can be undone
+    // without copying the whole selection.
+    dropped: Vec<usize>,
+}
+
+impl<'a> Annealer<'a> {
+    // Start from the greedy solution.
+    fn new(items: &'a [Item], allowed_weight: i32, config: &AnnealingConfig) -> Self {
+        let order = ratio_order(items);
+        // Overweight selections must never score better than feasible
+        // ones nearby, so the penalty is at least the best ratio.
+        let penalty = match (config.infeasible, order.first()) {
+            (Infeasible::Penalty(factor), Some(&i)) => {
+                factor * items[i].value as f64 / items[i].weight.max(1) as f64
+            }
+            _ => 0.0,
+        };
+        let (start, value, _) = greedy(&mut copy_items(items), allowed_weight);
+        let state = State {
+            selected: start.iter().map(|item| item.is_selected).collect(),
+            value,
+            weight: sum_weights(&start, false),
+        };
+        Self {
+            items,
+            allowed_weight,
+            order,
+            swap_probability: config.swap_probability,
+            infeasible: config.infeasible,
+            penalty,
+            state,
+            dropped: vec![],
+        }
+    }
+
+    fn score(&self) -> f64 {
+        let excess = (self.state.weight - self.allowed_weight).max(0);
+        self.state.value as f64 - self.penalty * excess as f64
+    }
+
+    // Make a random move and keep it with the annealing probability.
+    // Return true if the move was kept.
+    fn step(&mut self, prng: &mut Prng, temperature: f64) -> bool {
+        let old_score = self.score();
+        let (old_value, old_weight) = (self.state.value, self.state.weight);
+
+        let i = prng.next_i32(0, self.items.len() as i32) as usize;
+        let j = prng.next_i32(0, self.items.len() as i32) as usize;
+        let swap = prng.next_f64() < self.swap_probability
+            && self.state.selected[i] != self.state.selected[j];
+        self.state.flip(self.items, i);
+        if swap {
+            self.state.flip(self.items, j);
+        }
+        self.dropped.clear();
+        if self.infeasible == Infeasible::Repair {
+            (self.state.value, self.state.weight) = repair_recording(
+                self.items,
+                &mut self.state.selected,
+                self.allowed_weight,
+                &self.order,
+                self.state.value,
+                self.state.weight,
+                &mut self.dropped,
+            );
+        }
+
+        let delta = self.score() - old_score;
+        if delta >= 0.0 || prng.next_f64() < (delta / temperature).exp() {
+            return true;
+        }
+
+        // Undo the repair, then the move.
+        for &k in self.dropped.iter() {
+            self.state.selected[k] = true;
+        }
+        self.state.selected[i] = !self.state.selected[i];
+        if swap {
+            self.state.selected[j] = !self.state.selected[j];
+        }
+        (self.state.value, self.state.weight) = (old_value, old_weight);
+        false
+    }
+}
+
+// Anneal with the default configuration.
+// Return the best assignment, its value and the number of iterations.
+pub fn simulated_annealing(items: &mut [Item], allowed_weight: i32) -> Solution {
+    simulated_annealing_with(items, allowed_weight, &AnnealingConfig::default()).into_solution()
+}
+
+pub fn simulated_annealing_with(
+    items: &mut [Item],
+    allowed_weight: i32,
+    config: &AnnealingConfig,
+) -> SearchResult {
+    let mut prng = Prng::new_with_seed(config.seed);
+    let upper_bound = lp_dual(items, allowed_weight).bound(items, allowed_weight);
+    let mut annealer = Annealer::new(items, allowed_weight, config);
+    let mut best_selected = annealer.state.selected.clone();
+    let mut best_value = annealer.state.value;
+
+    let mean_value = match items.len() {
+        0 => 1.0,
+        n => items.iter().map(|item| item.value as f64).sum::<f64>() / n as f64,
+    };
+
+    let mut iterations = 0;
+    while iterations < config.iterations && !items.is_empty() {
+        let progress = iterations as f64 / config.iterations as f64;
+        let temperature = config.schedule.temperature(progress) * mean_value;
+        iterations += 1;
+
+        if annealer.step(&mut prng, temperature)
+            && annealer.state.weight <= allowed_weight
+            && annealer.state.value > best_value
+        {
+            best_value = annealer.state.value;
+            best_selected.clone_from(&annealer.state.selected);
+        }
+    }
+
+    let mut solution = copy_items(items);
+    for (item, &selected) in solution.iter_mut().zip(&best_selected) {
+        item.is_selected = selected;
+    }
+    SearchResult {
+        items: solution,
+        value: best_value,
+        calls: iterations,
+        upper_bound,
+        proven_optimal: best_value == upper_bound,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dp::dynamic_programming;
+    use crate::item::{make_item, make_test_instance, sum_values};
+    use crate::verify::verify;
+
+    // Check that the state's value and weight match its selection.
+    fn assert_consistent(annealer: &Annealer) {
+        let (mut value, mut weight) = (0, 0);
+        for (item, &selected) in annealer.items.iter().zip(&annealer.state.selected) {
+            if selected {
+                value += item.value;
+                weight += item.weight;
+            }
+        }
+        assert_eq!(
+            (annealer.state.value, annealer.state.weight),
+            (value, weight)
+        );
+    }
+
+    #[test]
+    fn repair_keeps_every_state_feasible() {
+        let (items, allowed_weight) = make_test_instance(3, 60);
+        let config = AnnealingConfig::default().with_infeasible(Infeasible::Repair);
+        let mut annealer = Annealer::new(&items, allowed_weight, &config);
+        let mut prng = Prng::new_with_seed(1);
+        let mut rejected = 0;
+        for _ in 0..2_000 {
+            // A low temperature rejects most moves that lose value.
+            if !annealer.step(&mut prng, 1.0) {
+                rejected += 1;
+            }
+            assert!(annealer.state.weight <= allowed_weight);
+            assert_consistent(&annealer);
+        }
+        assert!(rejected > 0);
+    }
+
+    #[test]
+    fn penalty_passes_through_overweight_states() {
+        let (items, allowed_weight) = make_test_instance(3, 60);
+        let config = AnnealingConfig::default().with_infeasible(Infeasible::Penalty(2.0));
+        let mut annealer = Annealer::new(&items, allowed_weight, &config);
+        let mut prng = Prng::new_with_seed(1);
+        let mut overweight = 0;
+        for _ in 0..2_000 {
+            // A high temperature accepts almost every move.
+            annealer.step(&mut prng, 1e6);
+            if annealer.state.weight > allowed_weight {
+                overweight += 1;
+                assert!(annealer.score() < annealer.state.value as f64);
+            }
+            assert_consistent(&annealer);
+        }
+        assert!(overweight > 0);
+    }
+
+    #[test]
+    fn both_modes_stay_between_greedy_and_the_optimum() {
+        for infeasible in [Infeasible::Penalty(2.0), Infeasible::Repair] {
+            let config = AnnealingConfig::default()
+                .with_iterations(20_000)
+                .with_infeasible(infeasible);
+            let (items, allowed_weight) = make_test_instance(5, 40);
+            let (_, greedy_value, _) = greedy(&mut copy_items(&items), allowed_weight);
+            let (_, optimum, _) = dynamic_programming(&mut copy_items(&items), allowed_weight);
+
+            let result = simulated_annealing_with(&mut copy_items(&items), allowed_weight, &config);
+            verify(&items, allowed_weight, &result.items, result.value).unwrap();
+            assert!(greedy_value <= result.value && result.value <= optimum);
+        }
+    }
+
+    #[test]
+    fn result_reports_iterations_and_the_lp_bound() {
+        let (items, allowed_weight) = make_test_instance(7, 200);
+        let config = AnnealingConfig::default().with_iterations(5_000);
+        let result = simulated_annealing_with(&mut copy_items(&items), allowed_weight, &config);
+        assert_eq!(result.calls, 5_000);
+        let bound = lp_dual(&items, allowed_weight).bound(&items, allowed_weight);
+        assert_eq!(result.upper_bound, bound);
+        assert_eq!(result.proven_optimal, result.value == bound);
+
+        // Everything fits, so greedy meets the bound right away.
+        let items = vec![make_item(3, 2), make_item(4, 5)];
+        let result = simulated_annealing_with(&mut copy_items(&items), 7, &config);
+        assert_eq!(result.value, sum_values(&items, true));
+        assert_eq!(result.upper_bound, result.value);
+        assert!(result.proven_optimal);
+
+        let result = simulated_annealing_with(&mut [], 7, &config);
+        assert_eq!((result.value, result.calls), (0, 0));
+    }
+
+    #[test]
+    fn schedules_fall_from_start_to_end() {
+        for schedule in [
+            Schedule::Geometric {
+                start: 2.0,
+                end: 0.02,
+            },
+            Schedule::Linear {
+                start: 2.0,
+                end: 0.02,
+            },
+        ] {
+            assert!((schedule.temperature(0.0) - 2.0).abs() < 1e-12);
+            assert!((schedule.temperature(1.0) - 0.02).abs() < 1e-12);
+            assert!(schedule.temperature(0.3) > schedule.temperature(0.6));
+        }
+    }
+}
@@ -0,0 +1,210 @@
+// Branch and bound search: exhaustive search that stops exploring a
+// subtree as soon as it can't beat the best solution found so far.
+
+use crate::item::{check_instance, copy_items, sum_values, Item, Solution};
+use crate::limits::{Budget, Limits, SearchResult};
+use crate::number::{Number, NumberError};
+use crate::trace::{Decision, Prune};
+
+// The best solution found so far and its value.
+pub(crate) struct Best<T = i32> {
+    pub(crate) items: Vec<Item<T>>,
+    pub(crate) value: T,
+}
+
+// Recursively assign values in or out of the solution.
+// Return the best assignment, value of that assignment,
+// and the number of function calls we made.
+pub fn branch_and_bound<T: Number>(items: &mut [Item<T>], allowed_weight: T) -> Solution<T> {
+    branch_and_bound_limited(items, allowed_weight, &Limits::none()).into_solution()
+}
+
+// Branch and bound that stops when a limit is hit and
+// returns the best solution it found until then.
+pub fn branch_and_bound_limited<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    limits: &Limits,
+) -> SearchResult<Item<T>, T> {
+    try_branch_and_bound_limited(items, allowed_weight, limits)
+        .unwrap_or_else(|error| panic!("branch and bound: {}", error))
+}
+
+// Branch and bound that returns an error if the items' totals don't fit into T.
+pub fn try_branch_and_bound_limited<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    limits: &Limits,
+) -> Result<SearchResult<Item<T>, T>, NumberError> {
+    check_instance(items, allowed_weight)?;
+    for item in items.iter_mut() {
+        item.is_selected = false;
+    }
+    let current_value = T::zero();
+    let current_weight = T::zero();
+    let remaining_value = sum_values(items, true);
+    let mut best = Best {
+        items: copy_items(items),
+        value: T::zero(),
+    };
+    let mut budget = Budget::new(limits);
+
+    do_branch_and_bound(
+        items,
+        allowed_weight,
+        0,
+        current_value,
+        current_weight,
+        remaining_value,
+        &mut best,
+        &mut budget,
+    );
+
+    Ok(budget.finish(best.items, best.value))
+}
+
+// The best solution is shared by the whole search, so a solution found
+// in one branch lets the search prune the branches it visits later.
+#[allow(clippy::too_many_arguments)]
+fn do_branch_and_bound<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    next_index: usize,
+    current_value: T,
+    current_weight: T,
+    remaining_value: T,
+    best: &mut Best<T>,
+    budget: &mut Budget<T>,
+) {
+    budget.trace_visit(
+        next_index,
+        Decision::of(items, next_index),
+        current_value,
+        current_weight,
+        current_value + remaining_value,
+        current_value > best.value,
+    );
+
+    // The current selection is a solution in its own right.
+    if current_value > best.value {
+        best.items = copy_items(items);
+        best.value = current_value;
+        budget.found(current_value);
+    }
+
+    if !budget.visit(next_index, || current_value + remaining_value) {
+        budget.trace_prune(next_index, Prune::Limit);
+        return;
+    }
+
+    if next_index >= items.len() {
+        return;
+    }
+
+    if current_value + remaining_value <= best.value {
+        budget.trace_prune(next_index, Prune::Bound);
+        return;
+    }
+
+    let value = items[next_index].value;
+    let weight = items[next_index].weight;
+
+    if current_weight + weight <= allowed_weight {
+        // Select next item:
+        items[next_index].is_selected = true;
+        do_branch_and_bound(
+            items,
+            allowed_weight,
+            next_index + 1,
+            current_value + value,
+            current_weight + weight,
+            remaining_value - value,
+            best,
+            budget,
+        );
+        items[next_index].is_selected = false;
+    } else {
+        budget.trace_pruned_child(
+            next_index + 1,
+            Decision::Take(next_index),
+            current_value + value,
+            current_weight + weight,
+            current_value + remaining_value,
+            Prune::Capacity,
+        );
+    }
+
+    if current_value + remaining_value - value > best.value {
+        // Don't select next item:
+        do_branch_and_bound(
+            items,
+            allowed_weight,
+            next_index + 1,
+            current_value,
+            current_weight,
+            remaining_value - value,
+            best,
+            budget,
+        );
+    } else {
+        budget.trace_pruned_child(
+            next_index + 1,
+            Decision::Skip(next_index),
+            current_value,
+            current_weight,
+            current_value + remaining_value - value,
+            Prune::Bound,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exhaustive::exhaustive_search;
+    use crate::item::{make_items, sum_weights};
+    use crate::ordering::{with_order, ItemOrder};
+    use crate::verify::verify;
+    use utils::{Generator, Prng};
+
+    #[test]
+    fn matches_exhaustive_search_in_every_order() {
+        for seed in 0..100 {
+            let mut prng = Prng::new_with_seed(seed);
+            let num_items = prng.next_i32(1, 15);
+            let items = make_items(&mut prng, num_items, 1, 10, 1, 10);
+            let allowed_weight = sum_weights(&items, true) / 2;
+
+            let (_, expected, _) = exhaustive_search(&mut copy_items(&items), allowed_weight);
+
+            for order in ItemOrder::all(seed) {
+                let (solution, value, _) =
+                    with_order(&branch_and_bound, order)(&mut copy_items(&items), allowed_weight);
+                assert_eq!(value, expected, "seed {seed}, order {}", order.name());
+                verify(&items, allowed_weight, &solution, value).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn node_limit_returns_incumbent_and_bound() {
+        let mut prng = Prng::new_with_seed(1337);
+        let items = make_items(&mut prng, 30, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        let (_, optimum, _) =
+            crate::dp::dynamic_programming(&mut copy_items(&items), allowed_weight);
+
+        let limits = Limits::none().with_nodes(100);
+        let result = branch_and_bound_limited(&mut copy_items(&items), allowed_weight, &limits);
+        assert_eq!(result.calls, 100);
+        assert!(!result.proven_optimal);
+        verify(&items, allowed_weight, &result.items, result.value).unwrap();
+        assert!(result.value <= optimum && optimum <= result.upper_bound);
+        assert!(result.gap() > 0.0);
+
+        let result =
+            branch_and_bound_limited(&mut copy_items(&items), allowed_weight, &Limits::none());
+        assert!(result.proven_optimal);
+        assert_eq!(result.value, optimum);
+    }
+}
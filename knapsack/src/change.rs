@@ -0,0 +1,156 @@
+// Change-making: pay an amount with coins whose denominations are the
+// items' weights. Every coin may be used any number of times.
+
+use crate::dp::{fill_table, item_weights, Multiplicity};
+use crate::item::Item;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeError {
+    // A coin worth 0 could be used any number of times
+    // without paying anything.
+    ZeroCoin,
+    // The number of ways doesn't fit into a u128.
+    Overflow,
+}
+
+impl fmt::Display for ChangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChangeError::ZeroCoin => write!(f, "every coin must be worth more than 0"),
+            ChangeError::Overflow => write!(f, "the number of ways doesn't fit into a u128"),
+        }
+    }
+}
+
+// Return the smaller of two coin counts, where None means unpayable.
+fn fewer_coins(a: Option<i32>, b: Option<i32>) -> Option<i32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+fn check_coins(coins: &[Item]) -> Result<(), ChangeError> {
+    if coins.iter().any(|coin| coin.weight == 0) {
+        return Err(ChangeError::ZeroCoin);
+    }
+    Ok(())
+}
+
+// Return the smallest number of coins that pays amount exactly
+// together with how often each coin is used, or None if the
+// amount can't be paid. Return an error if a coin is worth 0.
+pub fn min_coins(coins: &[Item], amount: i32) -> Result<Option<(i32, Vec<i32>)>, ChangeError> {
+    check_coins(coins)?;
+    if amount < 0 {
+        return Ok(None);
+    }
+    let capacity = amount as usize;
+    let table = fill_table(
+        &item_weights(coins),
+        capacity,
+        Multiplicity::Unbounded,
+        |c| if c == 0 { Some(0) } else { None },
+        |skip, take, _| fewer_coins(skip, take.flatten().map(|count| count + 1)),
+    );
+
+    let Some(num_coins) = table.get(coins.len(), capacity) else {
+        return Ok(None);
+    };
+
+    // Walk back: stay in a row while its coin was used,
+    // move up as soon as the row above has the same count.
+    let mut counts = vec![0; coins.len()];
+    let mut row = coins.len();
+    let mut c = capacity;
+    while c > 0 {
+        if table.get(row, c) == table.get(row - 1, c) {
+            row -= 1;
+        } else {
+            counts[row - 1] += 1;
+            c -= coins[row - 1].weight as usize;
+        }
+    }
+    Ok(Some((num_coins, counts)))
+}
+
+// Return the number of different ways to pay amount exactly.
+// The order of the coins doesn't matter. Return an error if a
+// coin is worth 0 or the number doesn't fit.
+pub fn count_ways(coins: &[Item], amount: i32) -> Result<u128, ChangeError> {
+    check_coins(coins)?;
+    if amount < 0 {
+        return Ok(0);
+    }
+    // A cell is None once its count has overflowed.
+    let capacity = amount as usize;
+    let table = fill_table(
+        &item_weights(coins),
+        capacity,
+        Multiplicity::Unbounded,
+        |c| Some(if c == 0 { 1u128 } else { 0 }),
+        |skip, take, _| match (skip, take.unwrap_or(Some(0))) {
+            (Some(skip), Some(take)) => skip.checked_add(take),
+            _ => None,
+        },
+    );
+    table
+        .get(coins.len(), capacity)
+        .ok_or(ChangeError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_coins(denominations: &[i32]) -> Vec<Item> {
+        denominations
+            .iter()
+            .map(|&weight| Item {
+                value: 1,
+                weight,
+                is_selected: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn min_coins_beats_greedy() {
+        // Greedy would pay 6 as 4 + 1 + 1.
+        let coins = make_coins(&[1, 3, 4]);
+        let (num_coins, counts) = min_coins(&coins, 6).unwrap().unwrap();
+        assert_eq!(num_coins, 2);
+        assert_eq!(counts, vec![0, 2, 0]);
+    }
+
+    #[test]
+    fn min_coins_unreachable() {
+        let coins = make_coins(&[4, 6]);
+        assert_eq!(min_coins(&coins, 7), Ok(None));
+        assert_eq!(min_coins(&coins, 0), Ok(Some((0, vec![0, 0]))));
+    }
+
+    #[test]
+    fn count_ways_of_us_coins() {
+        let coins = make_coins(&[1, 5, 10, 25]);
+        assert_eq!(count_ways(&coins, 100), Ok(242));
+        assert_eq!(count_ways(&coins, 0), Ok(1));
+    }
+
+    #[test]
+    fn zero_coins_are_rejected() {
+        let coins = make_coins(&[0, 2, 5]);
+        assert_eq!(min_coins(&coins, 7), Err(ChangeError::ZeroCoin));
+        assert_eq!(count_ways(&coins, 7), Err(ChangeError::ZeroCoin));
+    }
+
+    #[test]
+    fn too_many_ways_is_an_error() {
+        // 200 coins worth 1 pay 200 in C(399, 199) ways.
+        let coins = make_coins(&[1; 200]);
+        assert_eq!(count_ways(&coins, 200), Err(ChangeError::Overflow));
+        assert_eq!(count_ways(&coins[..2], 200), Ok(201));
+    }
+}
@@ -0,0 +1,187 @@
+// Dominance between items, the idea behind Rod's technique.
+
+use crate::item::Item;
+use crate::number::{compare, Number};
+
+// Return true if item can block other, i.e. item is at least as
+// valuable and at most as heavy. If item is left out of a solution,
+// other can be left out too because item could replace it.
+pub fn can_block<T: Number>(item: &Item<T>, other: &Item<T>) -> bool {
+    item.value >= other.value && item.weight <= other.weight
+}
+
+// Return the block list of every item: the indices of the items it can block.
+pub fn init_block_lists<T: Number>(items: &[Item<T>]) -> Vec<Vec<usize>> {
+    let mut block_lists = vec![];
+    for (i, item) in items.iter().enumerate() {
+        let mut block_list = vec![];
+        for (j, other) in items.iter().enumerate() {
+            if i != j && can_block(item, other) {
+                block_list.push(j);
+            }
+        }
+        block_lists.push(block_list);
+    }
+    block_lists
+}
+
+// A Fenwick tree that counts the items inserted at each rank.
+struct Fenwick {
+    counts: Vec<usize>,
+}
+
+impl Fenwick {
+    fn new(size: usize) -> Self {
+        Self {
+            counts: vec![0; size + 1],
+        }
+    }
+
+    // Count one more item at rank.
+    fn add(&mut self, rank: usize) {
+        let mut i = rank + 1;
+        while i < self.counts.len() {
+            self.counts[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    // Return the number of items at ranks 0..=rank.
+    fn prefix_count(&self, rank: usize) -> usize {
+        let mut i = rank + 1;
+        let mut total = 0;
+        while i > 0 {
+            total += self.counts[i];
+            i -= i & i.wrapping_neg();
+        }
+        total
+    }
+}
+
+// Return the length of every item's block list without building the lists.
+//
+// Sweep the items from heavy to light and insert their value ranks into a
+// Fenwick tree. When an item is reached, the tree holds every item that is
+// at least as heavy, and the items it can block are those among them with
+// at most its value. O(n log n).
+pub fn block_counts<T: Number>(items: &[Item<T>]) -> Vec<usize> {
+    let mut values: Vec<T> = items.iter().map(|item| item.value).collect();
+    values.sort_unstable_by(compare);
+    values.dedup();
+    let value_rank = |value: T| values.partition_point(|&v| v < value);
+
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| compare(&items[b].weight, &items[a].weight));
+
+    let mut tree = Fenwick::new(values.len());
+    let mut counts = vec![0; items.len()];
+    let mut start = 0;
+    while start < order.len() {
+        // Insert all items of the same weight before counting,
+        // because they can block each other.
+        let weight = items[order[start]].weight;
+        let mut end = start;
+        while end < order.len() && items[order[end]].weight == weight {
+            tree.add(value_rank(items[order[end]].value));
+            end += 1;
+        }
+        for &i in order[start..end].iter() {
+            // Don't count the item itself.
+            counts[i] = tree.prefix_count(value_rank(items[i].value)) - 1;
+        }
+        start = end;
+    }
+    counts
+}
+
+// Return the same block lists as init_block_lists, but with each list in
+// no particular order, in O((n + k) log n) where k is the total length:
+// reporting the k_i items of one list costs O((k_i + 1) log n).
+//
+// Sort the items by value. The items an item can block are a prefix of
+// that order (value at most its value) restricted to weights at least its
+// weight. A segment tree over that order holds the smallest and largest
+// weight of each range, so we can skip ranges where no weight qualifies
+// and report ranges where every weight qualifies without descending.
+pub fn sweep_block_lists<T: Number>(items: &[Item<T>]) -> Vec<Vec<usize>> {
+    let n = items.len();
+    let mut by_value: Vec<usize> = (0..n).collect();
+    by_value.sort_by(|&a, &b| compare(&items[a].value, &items[b].value));
+
+    let mut size = 1;
+    while size < n {
+        size *= 2;
+    }
+    // Leaves past the last item hold no weight.
+    let mut min_weights: Vec<Option<T>> = vec![None; 2 * size];
+    let mut max_weights: Vec<Option<T>> = vec![None; 2 * size];
+    for (position, &i) in by_value.iter().enumerate() {
+        min_weights[size + position] = Some(items[i].weight);
+        max_weights[size + position] = Some(items[i].weight);
+    }
+    for node in (1..size).rev() {
+        min_weights[node] = pick(min_weights[2 * node], min_weights[2 * node + 1], |a, b| {
+            b < a
+        });
+        max_weights[node] = pick(max_weights[2 * node], max_weights[2 * node + 1], |a, b| {
+            b > a
+        });
+    }
+
+    let mut block_lists = vec![];
+    for (i, item) in items.iter().enumerate() {
+        let prefix = by_value.partition_point(|&j| items[j].value <= item.value);
+        let mut block_list = vec![];
+        let mut stack = vec![(1, 0, size)];
+        while let Some((node, low, high)) = stack.pop() {
+            if low >= prefix || !matches!(max_weights[node], Some(max) if max >= item.weight) {
+                continue;
+            }
+            if high <= prefix && matches!(min_weights[node], Some(min) if min >= item.weight) {
+                let others = by_value[low..high].iter().filter(|&&j| j != i);
+                block_list.extend(others);
+                continue;
+            }
+            let middle = (low + high) / 2;
+            stack.push((2 * node + 1, middle, high));
+            stack.push((2 * node, low, middle));
+        }
+        block_lists.push(block_list);
+    }
+    block_lists
+}
+
+// Return b if a is None or better(a, b), else a.
+fn pick<T: Copy>(a: Option<T>, b: Option<T>, better: impl Fn(T, T) -> bool) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) if !better(a, b) => Some(a),
+        (Some(a), None) => Some(a),
+        (_, b) => b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::make_items;
+    use utils::{Generator, Prng};
+
+    #[test]
+    fn sweep_matches_pairwise_comparison() {
+        for seed in 0..50 {
+            let mut prng = Prng::new_with_seed(seed);
+            let num_items = prng.next_i32(0, 60);
+            let items = make_items(&mut prng, num_items, 1, 10, 4, 10);
+
+            let expected = init_block_lists(&items);
+            let mut block_lists = sweep_block_lists(&items);
+            for block_list in block_lists.iter_mut() {
+                block_list.sort_unstable();
+            }
+            assert_eq!(block_lists, expected, "seed {seed}");
+
+            let lengths: Vec<usize> = expected.iter().map(|list| list.len()).collect();
+            assert_eq!(block_counts(&items), lengths, "seed {seed}");
+        }
+    }
+}
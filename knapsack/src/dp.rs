@@ -0,0 +1,237 @@
+// The dynamic programming engine.
+//
+// A table has one row per prefix of the items (row 0 is the empty prefix)
+// and one column per capacity 0..=capacity. Row i + 1 is computed from
+// row i by a step function that decides between skipping item i and
+// taking it. The knapsack, subset sum and change-making solvers only
+// differ in the cell type, the base row and the step function.
+
+use crate::item::{check_instance, make_empty_selection, Item, Solution};
+use crate::number::{Number, NumberError};
+
+// How often an item may be taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Multiplicity {
+    // Each item may be taken at most once (0/1 knapsack, subset sum).
+    ZeroOne,
+    // Each item may be taken any number of times (change-making).
+    Unbounded,
+}
+
+pub struct DpTable<T> {
+    num_columns: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Copy> DpTable<T> {
+    pub fn num_rows(&self) -> usize {
+        self.cells.len() / self.num_columns
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.num_columns
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> T {
+        self.cells[row * self.num_columns + column]
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        let start = row * self.num_columns;
+        &self.cells[start..start + self.num_columns]
+    }
+
+    // Return the number of cells in the table.
+    pub fn num_cells(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+// Fill a table for the given item weights.
+//
+// Row 0 holds base(column). For row i + 1 and column c the cell is
+// step(skip, take, i) where skip is the cell above and take is the cell
+// we come from when taking item i: the row above for ZeroOne items and
+// the same row for Unbounded items, c - weights[i] columns to the left.
+// take is None if the item doesn't fit into capacity c.
+pub fn fill_table<T: Copy>(
+    weights: &[usize],
+    capacity: usize,
+    multiplicity: Multiplicity,
+    base: impl Fn(usize) -> T,
+    step: impl Fn(T, Option<T>, usize) -> T,
+) -> DpTable<T> {
+    assert!(
+        multiplicity == Multiplicity::ZeroOne || !weights.contains(&0),
+        "Items that may be taken any number of times need a positive weight"
+    );
+    let num_columns = capacity + 1;
+    let mut cells: Vec<T> = Vec::with_capacity((weights.len() + 1) * num_columns);
+    cells.extend((0..num_columns).map(base));
+
+    for (i, &weight) in weights.iter().enumerate() {
+        let above = i * num_columns;
+        let current = above + num_columns;
+        for c in 0..num_columns {
+            let skip = cells[above + c];
+            let take = if weight > c {
+                None
+            } else {
+                match multiplicity {
+                    Multiplicity::ZeroOne => Some(cells[above + c - weight]),
+                    Multiplicity::Unbounded => Some(cells[current + c - weight]),
+                }
+            };
+            cells.push(step(skip, take, i));
+        }
+    }
+
+    DpTable { num_columns, cells }
+}
+
+// Return the items' weights as table offsets.
+// Panic if a weight can't be one; table_weights returns an error.
+pub fn item_weights<N: Number>(items: &[Item<N>]) -> Vec<usize> {
+    table_weights(items).unwrap_or_else(|error| panic!("{}", error))
+}
+
+// Return the items' weights as table offsets, or an error
+// if a weight isn't a whole non-negative number.
+pub fn table_weights<N: Number>(items: &[Item<N>]) -> Result<Vec<usize>, NumberError> {
+    items
+        .iter()
+        .map(|item| item.weight.to_index().ok_or(NumberError::NotAnIndex))
+        .collect()
+}
+
+// Walk back through a ZeroOne table starting at column and return
+// the column the walk passes in every row, indexed by row. The walk
+// moves left by an item's weight where the item's row differs from
+// the row above it, which means the item is selected.
+pub fn reconstruction_path<T: Copy + PartialEq, N: Number>(
+    table: &DpTable<T>,
+    items: &[Item<N>],
+    column: usize,
+) -> Vec<usize> {
+    let weights = item_weights(items);
+    let mut columns = vec![0; items.len() + 1];
+    let mut c = column;
+    for i in (0..items.len()).rev() {
+        columns[i + 1] = c;
+        if table.get(i + 1, c) != table.get(i, c) {
+            c -= weights[i];
+        }
+    }
+    columns[0] = c;
+    columns
+}
+
+// Walk back through a ZeroOne table starting at column and select
+// every item whose row differs from the row above it.
+pub fn reconstruct<T: Copy + PartialEq, N: Number>(
+    table: &DpTable<T>,
+    items: &[Item<N>],
+    column: usize,
+) -> Vec<Item<N>> {
+    let columns = reconstruction_path(table, items, column);
+    let mut solution = make_empty_selection(items);
+    for (i, item) in solution.iter_mut().enumerate() {
+        item.is_selected = columns[i + 1] != columns[i];
+    }
+    solution
+}
+
+// Solve the 0/1 knapsack problem with a table of best values.
+// Return the best assignment, its value and the number of table cells.
+pub fn dynamic_programming<T: Number>(items: &mut [Item<T>], allowed_weight: T) -> Solution<T> {
+    try_dynamic_programming(items, allowed_weight)
+        .unwrap_or_else(|error| panic!("dynamic programming: {}", error))
+}
+
+// Dynamic programming that returns an error if the items' totals don't
+// fit into T or the weights and allowed weight aren't whole numbers.
+pub fn try_dynamic_programming<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+) -> Result<Solution<T>, NumberError> {
+    let table = value_table(items, allowed_weight)?;
+    let capacity = table.num_columns() - 1;
+    let solution = reconstruct(&table, items, capacity);
+    let value = table.get(items.len(), capacity);
+    Ok((solution, value, table.num_cells() as i32))
+}
+
+// Return the table of best values: the cell in row i and column c
+// is the best value of the first i items with capacity c.
+pub fn value_table<T: Number>(
+    items: &[Item<T>],
+    allowed_weight: T,
+) -> Result<DpTable<T>, NumberError> {
+    check_instance(items, allowed_weight)?;
+    let capacity = allowed_weight.to_index().ok_or(NumberError::NotAnIndex)?;
+    Ok(fill_table(
+        &table_weights(items)?,
+        capacity,
+        Multiplicity::ZeroOne,
+        |_| T::zero(),
+        |skip, take, i| match take {
+            Some(value) if value + items[i].value > skip => value + items[i].value,
+            _ => skip,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::make_items;
+    use crate::verify::verify;
+    use utils::Prng;
+
+    #[test]
+    fn dynamic_programming_finds_optimum() {
+        let mut items = vec![
+            Item {
+                value: 6,
+                weight: 1,
+                is_selected: false,
+            },
+            Item {
+                value: 10,
+                weight: 2,
+                is_selected: false,
+            },
+            Item {
+                value: 12,
+                weight: 3,
+                is_selected: false,
+            },
+        ];
+        let (solution, value, _) = dynamic_programming(&mut items, 5);
+        assert_eq!(value, 22);
+        verify(&items, 5, &solution, 22).unwrap();
+        assert!(!solution[0].is_selected);
+    }
+
+    #[test]
+    fn unbounded_rows_reuse_items() {
+        // Count the ways to pay 4 with coins 1 and 2: 1111, 112, 22.
+        let table = fill_table(
+            &[1, 2],
+            4,
+            Multiplicity::Unbounded,
+            |c| if c == 0 { 1 } else { 0 },
+            |skip, take, _| skip + take.unwrap_or(0),
+        );
+        assert_eq!(table.get(2, 4), 3);
+        assert_eq!(table.num_rows(), 3);
+    }
+
+    #[test]
+    fn dynamic_programming_solution_is_consistent() {
+        let mut prng = Prng::new_with_seed(1337);
+        let mut items = make_items(&mut prng, 30, 1, 10, 4, 10);
+        let (solution, value, _) = dynamic_programming(&mut items, 100);
+        verify(&items, 100, &solution, value).unwrap();
+    }
+}
@@ -0,0 +1,355 @@
+// Render dynamic programming tables for teaching and debugging.
+//
+// Every renderer draws row i of the table as the prefix of the first
+// i items and the columns as capacities 0..=capacity. A path, as
+// reconstruction_path returns it, gives the column the reconstruction
+// passes in every row; those cells are highlighted. An empty path
+// highlights nothing.
+//
+// Cells are coloured by their heat: the cell value relative to the
+// smallest and largest value in the table. Cells without a value,
+// like unpayable amounts, stay blank.
+
+use crate::dp::DpTable;
+use std::io::{self, Write};
+
+// The size of an SVG cell and the margin for the labels, in pixels.
+const SVG_CELL: usize = 28;
+const SVG_MARGIN: usize = 64;
+
+// A value that can be drawn in a table cell.
+pub trait Cell: Copy {
+    fn text(self) -> String;
+
+    // The number the cell's colour is based on, or None for no colour.
+    fn heat(self) -> Option<f64>;
+}
+
+macro_rules! impl_cell {
+    ($($t:ty),*) => {
+        $(
+            impl Cell for $t {
+                fn text(self) -> String {
+                    self.to_string()
+                }
+
+                fn heat(self) -> Option<f64> {
+                    Some(self as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_cell!(i32, i64, u64, u128, f64);
+
+// Subset sum tables say whether a sum is reachable.
+impl Cell for bool {
+    fn text(self) -> String {
+        if self { "T" } else { "." }.to_string()
+    }
+
+    fn heat(self) -> Option<f64> {
+        Some(if self { 1.0 } else { 0.0 })
+    }
+}
+
+impl<T: Cell> Cell for Option<T> {
+    fn text(self) -> String {
+        self.map_or("-".to_string(), T::text)
+    }
+
+    fn heat(self) -> Option<f64> {
+        self.and_then(T::heat)
+    }
+}
+
+// The smallest and largest heat in the table.
+fn heat_range<T: Cell>(table: &DpTable<T>) -> (f64, f64) {
+    let mut range = (f64::INFINITY, f64::NEG_INFINITY);
+    for row in 0..table.num_rows() {
+        for &cell in table.row(row) {
+            if let Some(heat) = cell.heat() {
+                range = (range.0.min(heat), range.1.max(heat));
+            }
+        }
+    }
+    range
+}
+
+// Return the cell's heat scaled to 0..=1.
+fn scaled_heat<T: Cell>(cell: T, (min, max): (f64, f64)) -> Option<f64> {
+    let heat = cell.heat()?;
+    if max > min {
+        Some((heat - min) / (max - min))
+    } else {
+        Some(0.0)
+    }
+}
+
+// Blend from white for t = 0 to steel blue for t = 1.
+fn heat_color(t: f64) -> (u8, u8, u8) {
+    let blend = |from: f64, to: f64| (from + (to - from) * t).round() as u8;
+    (blend(255.0, 40.0), blend(255.0, 90.0), blend(255.0, 160.0))
+}
+
+fn is_on_path(path: &[usize], row: usize, column: usize) -> bool {
+    path.get(row) == Some(&column)
+}
+
+fn row_label(row: usize) -> String {
+    match row {
+        0 => "none".to_string(),
+        row => format!("item {}", row - 1),
+    }
+}
+
+// Write the table with a header of capacities and a row per prefix.
+// Cells on the path end in '*'.
+pub fn write_csv<T: Cell>(
+    out: &mut impl Write,
+    table: &DpTable<T>,
+    path: &[usize],
+) -> io::Result<()> {
+    let columns: Vec<String> = (0..table.num_columns()).map(|c| c.to_string()).collect();
+    writeln!(out, "row,{}", columns.join(","))?;
+    for row in 0..table.num_rows() {
+        let cells: Vec<String> = table
+            .row(row)
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| match is_on_path(path, row, column) {
+                true => format!("{}*", cell.text()),
+                false => cell.text(),
+            })
+            .collect();
+        writeln!(out, "{},{}", row_label(row), cells.join(","))?;
+    }
+    Ok(())
+}
+
+// Write the table with ANSI colours for a terminal. The cells are
+// shaded by heat, the path is bold on yellow. Only the first
+// max_columns columns are shown, so wide tables fit on the screen.
+pub fn write_heatmap<T: Cell>(
+    out: &mut impl Write,
+    table: &DpTable<T>,
+    path: &[usize],
+    max_columns: usize,
+) -> io::Result<()> {
+    let range = heat_range(table);
+    let num_columns = table.num_columns().min(max_columns);
+    let width = (0..table.num_rows())
+        .flat_map(|row| table.row(row)[..num_columns].iter())
+        .map(|cell| cell.text().len())
+        .chain([num_columns.saturating_sub(1).to_string().len()])
+        .max()
+        .unwrap_or(1);
+    let label_width = row_label(table.num_rows() - 1).len();
+
+    write!(out, "{:>label_width$} ", "")?;
+    for column in 0..num_columns {
+        write!(out, " {:>width$}", column)?;
+    }
+    if num_columns < table.num_columns() {
+        write!(out, " ...")?;
+    }
+    writeln!(out)?;
+
+    for row in 0..table.num_rows() {
+        write!(out, "{:>label_width$} ", row_label(row))?;
+        for (column, &cell) in table.row(row)[..num_columns].iter().enumerate() {
+            let text = format!(" {:>width$}", cell.text());
+            if is_on_path(path, row, column) {
+                write!(out, "\x1b[1;30;103m{}\x1b[0m", text)?;
+                continue;
+            }
+            match scaled_heat(cell, range) {
+                Some(t) => {
+                    let (r, g, b) = heat_color(t);
+                    let foreground = if t > 0.6 { 97 } else { 30 };
+                    write!(
+                        out,
+                        "\x1b[{};48;2;{};{};{}m{}\x1b[0m",
+                        foreground, r, g, b, text
+                    )?;
+                }
+                None => write!(out, "{}", text)?,
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+// Write the table as a standalone SVG image. The path's cells
+// get a red border and are joined by a red line.
+pub fn write_svg<T: Cell>(
+    out: &mut impl Write,
+    table: &DpTable<T>,
+    path: &[usize],
+) -> io::Result<()> {
+    let range = heat_range(table);
+    let width = SVG_MARGIN + table.num_columns() * SVG_CELL;
+    let height = SVG_CELL + table.num_rows() * SVG_CELL;
+    let center = |index: usize, offset: usize| offset + index * SVG_CELL + SVG_CELL / 2;
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         font-family=\"monospace\" font-size=\"10\" text-anchor=\"middle\">",
+        width, height
+    )?;
+    writeln!(out, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+    for column in 0..table.num_columns() {
+        writeln!(
+            out,
+            "<text x=\"{}\" y=\"{}\">{}</text>",
+            center(column, SVG_MARGIN),
+            SVG_CELL * 2 / 3,
+            column
+        )?;
+    }
+    for row in 0..table.num_rows() {
+        let y = SVG_CELL + row * SVG_CELL;
+        writeln!(
+            out,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+            SVG_MARGIN - 6,
+            center(row, SVG_CELL) + 4,
+            row_label(row)
+        )?;
+        for (column, &cell) in table.row(row).iter().enumerate() {
+            let x = SVG_MARGIN + column * SVG_CELL;
+            let fill = match scaled_heat(cell, range) {
+                Some(t) => {
+                    let (r, g, b) = heat_color(t);
+                    format!("rgb({},{},{})", r, g, b)
+                }
+                None => "white".to_string(),
+            };
+            let (stroke, stroke_width) = match is_on_path(path, row, column) {
+                true => ("red", 2),
+                false => ("lightgray", 1),
+            };
+            writeln!(
+                out,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" \
+                 stroke=\"{}\" stroke-width=\"{}\"/>",
+                x, y, SVG_CELL, SVG_CELL, fill, stroke, stroke_width
+            )?;
+            let color = match scaled_heat(cell, range) {
+                Some(t) if t > 0.6 => "white",
+                _ => "black",
+            };
+            writeln!(
+                out,
+                "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>",
+                center(column, SVG_MARGIN),
+                center(row, SVG_CELL) + 4,
+                color,
+                cell.text()
+            )?;
+        }
+    }
+    if !path.is_empty() {
+        let points: Vec<String> = path
+            .iter()
+            .enumerate()
+            .map(|(row, &column)| {
+                format!("{},{}", center(column, SVG_MARGIN), center(row, SVG_CELL))
+            })
+            .collect();
+        writeln!(
+            out,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"red\" stroke-width=\"2\" opacity=\"0.6\"/>",
+            points.join(" ")
+        )?;
+    }
+    writeln!(out, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dp::{fill_table, reconstruction_path, value_table, Multiplicity};
+    use crate::item::Item;
+
+    fn make_items() -> Vec<Item> {
+        [(6, 1), (10, 2), (12, 3)]
+            .iter()
+            .map(|&(value, weight)| Item {
+                value,
+                weight,
+                is_selected: false,
+            })
+            .collect()
+    }
+
+    fn render(f: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut out = vec![];
+        f(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn path_follows_the_selected_items() {
+        let items = make_items();
+        let table = value_table(&items, 5).unwrap();
+        // Items 1 and 2 are selected: 5 -> 3 -> 0.
+        assert_eq!(reconstruction_path(&table, &items, 5), vec![0, 0, 2, 5]);
+    }
+
+    #[test]
+    fn csv_marks_the_path() {
+        let items = make_items();
+        let table = value_table(&items, 5).unwrap();
+        let path = reconstruction_path(&table, &items, 5);
+        let csv = render(|out| write_csv(out, &table, &path));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "row,0,1,2,3,4,5");
+        assert_eq!(lines[1], "none,0*,0,0,0,0,0");
+        assert_eq!(lines[3], "item 1,0,6,10*,16,16,16");
+        assert_eq!(lines[4], "item 2,0,6,10,16,18,22*");
+        assert_eq!(csv.matches('*').count(), path.len());
+    }
+
+    #[test]
+    fn heatmap_fits_the_columns() {
+        let items = make_items();
+        let table = value_table(&items, 5).unwrap();
+        let path = reconstruction_path(&table, &items, 5);
+        let heatmap = render(|out| write_heatmap(out, &table, &path, 3));
+        let lines: Vec<&str> = heatmap.lines().collect();
+        assert_eq!(lines.len(), table.num_rows() + 1);
+        assert!(lines[0].ends_with("0  1  2 ..."));
+        // Only the path cells in the first 3 columns are highlighted.
+        assert_eq!(heatmap.matches("\x1b[1;30;103m").count(), 3);
+    }
+
+    #[test]
+    fn svg_draws_every_cell() {
+        // Coin counts, where unpayable amounts have no value.
+        let table = fill_table(
+            &[2, 3],
+            4,
+            Multiplicity::Unbounded,
+            |c| if c == 0 { Some(0) } else { None },
+            |skip: Option<i32>, take: Option<Option<i32>>, _| {
+                skip.or(take.flatten().map(|count| count + 1))
+            },
+        );
+        let svg = render(|out| write_svg(out, &table, &[]));
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect x=").count(), table.num_cells());
+        assert!(svg.contains(">-</text>"));
+        assert!(!svg.contains("polyline"));
+
+        let items = make_items();
+        let table = value_table(&items, 5).unwrap();
+        let path = reconstruction_path(&table, &items, 5);
+        let svg = render(|out| write_svg(out, &table, &path));
+        assert_eq!(svg.matches("stroke=\"red\"").count(), path.len() + 1);
+    }
+}
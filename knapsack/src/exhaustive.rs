@@ -0,0 +1,100 @@
+// Exhaustive search tries every assignment of the items.
+// It is slow, but it is the reference the other solvers are checked against.
+
+use crate::item::{check_instance, copy_items, feasible_value, sum_values, Item, Solution};
+use crate::limits::{Budget, Limits, SearchResult};
+use crate::number::{checked_total, Number, NumberError};
+
+// Recursively assign values in or out of the solution.
+// Return the best assignment, value of that assignment,
+// and the number of function calls we made.
+pub fn exhaustive_search<T: Number>(items: &mut [Item<T>], allowed_weight: T) -> Solution<T> {
+    exhaustive_search_limited(items, allowed_weight, &Limits::none()).into_solution()
+}
+
+// Exhaustive search that stops when a limit is hit and
+// returns the best solution it found until then.
+pub fn exhaustive_search_limited<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    limits: &Limits,
+) -> SearchResult<Item<T>, T> {
+    try_exhaustive_search_limited(items, allowed_weight, limits)
+        .unwrap_or_else(|error| panic!("exhaustive search: {}", error))
+}
+
+// Exhaustive search that returns an error if the items' totals don't fit into T.
+pub fn try_exhaustive_search_limited<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    limits: &Limits,
+) -> Result<SearchResult<Item<T>, T>, NumberError> {
+    check_instance(items, allowed_weight)?;
+    for item in items.iter_mut() {
+        item.is_selected = false;
+    }
+    let mut budget = Budget::new(limits);
+    let (solution, value) = do_exhaustive_search(items, allowed_weight, 0, &mut budget);
+    // The path that skips every item keeps the empty selection, so
+    // some leaf always fits.
+    let value = value.expect("The empty selection fits");
+    Ok(budget.finish(solution, value))
+}
+
+fn do_exhaustive_search<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    next_index: i32,
+    budget: &mut Budget<T>,
+) -> (Vec<Item<T>>, Option<T>) {
+    // Nothing in this subtree is worth more than the items selected
+    // so far plus all the items that are still undecided.
+    let bound = || {
+        let undecided = checked_total(items[next_index as usize..].iter().map(|item| item.value));
+        sum_values(items, false) + undecided.expect("The totals were checked")
+    };
+    if !budget.visit(next_index as usize, bound) {
+        // Leave the undecided items out.
+        return (copy_items(items), feasible_value(items, allowed_weight));
+    }
+
+    if next_index >= items.len() as i32 {
+        let value = feasible_value(items, allowed_weight);
+        if let Some(value) = value {
+            budget.found(value);
+        }
+        return (copy_items(items), value);
+    }
+
+    let mut new_items_2 = copy_items(items);
+
+    // Skip next item:
+    let (solution_1, value_1) = do_exhaustive_search(items, allowed_weight, next_index + 1, budget);
+
+    // Select next item:
+    new_items_2[next_index as usize].is_selected = true;
+    let (solution_2, value_2) =
+        do_exhaustive_search(&mut new_items_2, allowed_weight, next_index + 1, budget);
+
+    // Return the best solution.
+    if value_1 > value_2 {
+        (solution_1, value_1)
+    } else {
+        (solution_2, value_2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::make_item;
+
+    #[test]
+    fn ignores_a_preselected_selection() {
+        let mut items = vec![make_item(3, 10), make_item(2, 4)];
+        items[0].is_selected = true;
+        let (solution, value, _) = exhaustive_search(&mut items, 5);
+        assert_eq!(value, 2);
+        assert!(!solution[0].is_selected && solution[1].is_selected);
+    }
+}
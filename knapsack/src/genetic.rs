@@ -0,0 +1,361 @@
+// A genetic algorithm for the 0/1 knapsack problem.
+//
+// A chromosome is a bitstring with one bit per item. Each generation
+// picks parents by tournament, crosses them over and flips random bits.
+// A child that is overweight is repaired by dropping its worst ratio
+// items until it fits, so every chromosome is a feasible selection and
+// its fitness is simply its value. The best chromosomes (the elites)
+// go on to the next generation unchanged.
+//
+// The first population holds the greedy solution and random
+// chromosomes. A seeded Prng makes every run reproducible.
+
+use crate::greedy::{greedy, ratio_order, repair};
+use crate::item::{copy_items, sum_weights, Item, Solution};
+use crate::limits::SearchResult;
+use crate::verify::lp_dual;
+use std::cmp::Reverse;
+use utils::{Generator, Prng};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crossover {
+    // Take every bit from either parent.
+    Uniform,
+    // Take the bits before a random cut from one parent
+    // and the rest from the other.
+    OnePoint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneticConfig {
+    pub population_size: usize,
+    pub generations: i32,
+    // The number of chromosomes a tournament picks the fittest from.
+    pub tournament_size: usize,
+    pub crossover: Crossover,
+    // The probability that two parents are crossed over
+    // instead of copying the first one.
+    pub crossover_rate: f64,
+    // The probability that a child's bit is flipped.
+    pub mutation_rate: f64,
+    // The number of best chromosomes kept unchanged.
+    pub elites: usize,
+    pub seed: u32,
+}
+
+impl Default for GeneticConfig {
+    fn default() -> Self {
+        Self {
+            population_size: 100,
+            generations: 200,
+            tournament_size: 3,
+            crossover: Crossover::Uniform,
+            crossover_rate: 0.9,
+            mutation_rate: 0.01,
+            elites: 2,
+            seed: 1337,
+        }
+    }
+}
+
+impl GeneticConfig {
+    pub fn with_population_size(self, population_size: usize) -> Self {
+        Self {
+            population_size,
+            ..self
+        }
+    }
+
+    pub fn with_generations(self, generations: i32) -> Self {
+        Self {
+            generations,
+            ..self
+        }
+    }
+
+    pub fn with_crossover(self, crossover: Crossover, crossover_rate: f64) -> Self {
+        Self {
+            crossover,
+            crossover_rate,
+            ..self
+        }
+    }
+
+    pub fn with_mutation_rate(self, mutation_rate: f64) -> Self {
+        Self {
+            mutation_rate,
+            ..self
+        }
+    }
+
+    pub fn with_tournament_size(self, tournament_size: usize) -> Self {
+        Self {
+            tournament_size,
+            ..self
+        }
+    }
+
+    pub fn with_elites(self, elites: usize) -> Self {
+        Self { elites, ..self }
+    }
+
+    pub fn with_seed(self, seed: u32) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+#[derive(Clone)]
+struct Chromosome {
+    genes: Vec<bool>,
+    value: i32,
+}
+
+// The parts of the problem every operator needs.
+struct Problem<'a> {
+    items: &'a [Item],
+    allowed_weight: i32,
+    order: Vec<usize>,
+}
+
+impl Problem<'_> {
+    // Repair the genes and make them a chromosome.
+    fn chromosome(&self, mut genes: Vec<bool>) -> Chromosome {
+        let (mut value, mut weight) = (0, 0);
+        for (item, &gene) in self.items.iter().zip(&genes) {
+            if gene {
+                value += item.value;
+                weight += item.weight;
+            }
+        }
+        let (value, _) = repair(
+            self.items,
+            &mut genes,
+            self.allowed_weight,
+            &self.order,
+            value,
+            weight,
+        );
+        Chromosome { genes, value }
+    }
+}
+
+fn random_index(prng: &mut Prng, len: usize) -> usize {
+    prng.next_i32(0, len as i32) as usize
+}
+
+// Return the fittest of tournament_size random chromosomes.
+fn tournament<'a>(
+    prng: &mut Prng,
+    population: &'a [Chromosome],
+    tournament_size: usize,
+) -> &'a Chromosome {
+    let mut winner = &population[random_index(prng, population.len())];
+    for _ in 1..tournament_size {
+        let other = &population[random_index(prng, population.len())];
+        if other.value > winner.value {
+            winner = other;
+        }
+    }
+    winner
+}
+
+fn crossover(prng: &mut Prng, a: &[bool], b: &[bool], kind: Crossover) -> Vec<bool> {
+    match kind {
+        Crossover::Uniform => a
+            .iter()
+            .zip(b)
+            .map(|(&a, &b)| if prng.next_f64() < 0.5 { a } else { b })
+            .collect(),
+        Crossover::OnePoint if a.len() < 2 => a.to_vec(),
+        Crossover::OnePoint => {
+            let cut = prng.next_i32(1, a.len() as i32) as usize;
+            a[..cut].iter().chain(&b[cut..]).copied().collect()
+        }
+    }
+}
+
+// Flip every gene with probability mutation_rate.
+fn mutate(prng: &mut Prng, genes: &mut [bool], mutation_rate: f64) {
+    for gene in genes.iter_mut() {
+        if prng.next_f64() < mutation_rate {
+            *gene = !*gene;
+        }
+    }
+}
+
+// Run the genetic algorithm with the default configuration.
+// Return the best assignment, its value and the number of chromosomes made.
+pub fn genetic_algorithm(items: &mut [Item], allowed_weight: i32) -> Solution {
+    genetic_algorithm_with(items, allowed_weight, &GeneticConfig::default()).into_solution()
+}
+
+pub fn genetic_algorithm_with(
+    items: &mut [Item],
+    allowed_weight: i32,
+    config: &GeneticConfig,
+) -> SearchResult {
+    assert!(
+        config.population_size > 0 && config.tournament_size > 0,
+        "The population and tournaments need a chromosome"
+    );
+    let mut prng = Prng::new_with_seed(config.seed);
+    let problem = Problem {
+        items,
+        allowed_weight,
+        order: ratio_order(items),
+    };
+    let upper_bound = lp_dual(items, allowed_weight).bound(items, allowed_weight);
+
+    // Random chromosomes select about as much weight as fits.
+    let total_weight = sum_weights(items, true);
+    let density = match total_weight {
+        0 => 1.0,
+        total_weight => (allowed_weight as f64 / total_weight as f64).min(1.0),
+    };
+    let (start, _, _) = greedy(&mut copy_items(items), allowed_weight);
+    let mut population =
+        vec![problem.chromosome(start.iter().map(|item| item.is_selected).collect())];
+    while population.len() < config.population_size {
+        let genes = (0..items.len())
+            .map(|_| prng.next_f64() < density)
+            .collect();
+        population.push(problem.chromosome(genes));
+    }
+    let mut made = population.len() as i32;
+
+    for _ in 0..config.generations {
+        population.sort_by_key(|chromosome| Reverse(chromosome.value));
+        let mut next: Vec<Chromosome> = population
+            .iter()
+            .take(config.elites.min(config.population_size))
+            .cloned()
+            .collect();
+        while next.len() < config.population_size {
+            let a = tournament(&mut prng, &population, config.tournament_size);
+            let b = tournament(&mut prng, &population, config.tournament_size);
+            let mut genes = if prng.next_f64() < config.crossover_rate {
+                crossover(&mut prng, &a.genes, &b.genes, config.crossover)
+            } else {
+                a.genes.clone()
+            };
+            mutate(&mut prng, &mut genes, config.mutation_rate);
+            next.push(problem.chromosome(genes));
+            made += 1;
+        }
+        population = next;
+    }
+
+    let best = population
+        .iter()
+        .reduce(|best, other| {
+            if other.value > best.value {
+                other
+            } else {
+                best
+            }
+        })
+        .expect("The population isn't empty");
+    let mut solution = copy_items(items);
+    for (item, &gene) in solution.iter_mut().zip(&best.genes) {
+        item.is_selected = gene;
+    }
+    SearchResult {
+        items: solution,
+        value: best.value,
+        calls: made,
+        upper_bound,
+        proven_optimal: best.value == upper_bound,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dp::dynamic_programming;
+    use crate::item::make_test_instance;
+    use crate::verify::verify;
+
+    #[test]
+    fn evolution_stays_between_greedy_and_the_optimum() {
+        let (items, allowed_weight) = make_test_instance(5, 40);
+        let (_, greedy_value, _) = greedy(&mut copy_items(&items), allowed_weight);
+        let (_, optimum, _) = dynamic_programming(&mut copy_items(&items), allowed_weight);
+
+        let config = GeneticConfig::default().with_generations(50);
+        let result = genetic_algorithm_with(&mut copy_items(&items), allowed_weight, &config);
+        verify(&items, allowed_weight, &result.items, result.value).unwrap();
+        assert!(greedy_value <= result.value && result.value <= optimum);
+        // The first population, then all but the elites in every generation.
+        assert_eq!(result.calls, 100 + 50 * (100 - 2));
+    }
+
+    #[test]
+    fn elites_keep_the_best_value() {
+        // Every child is the complement of its parent, so without
+        // elites the greedy solution is lost in the first generation.
+        let (items, allowed_weight) = make_test_instance(3, 40);
+        let (_, greedy_value, _) = greedy(&mut copy_items(&items), allowed_weight);
+        let config = GeneticConfig::default()
+            .with_generations(5)
+            .with_crossover(Crossover::Uniform, 0.0)
+            .with_mutation_rate(1.0);
+
+        let kept = genetic_algorithm_with(&mut copy_items(&items), allowed_weight, &config);
+        assert!(kept.value >= greedy_value);
+
+        let config = config.with_elites(0);
+        let lost = genetic_algorithm_with(&mut copy_items(&items), allowed_weight, &config);
+        assert!(lost.value < greedy_value);
+    }
+
+    #[test]
+    fn tournaments_pick_the_fittest_of_their_entrants() {
+        let population: Vec<Chromosome> = (0..10)
+            .map(|value| Chromosome {
+                genes: vec![],
+                value,
+            })
+            .collect();
+        let mut prng = Prng::new_with_seed(1);
+
+        // Large tournaments almost surely include the fittest chromosome.
+        for _ in 0..20 {
+            assert_eq!(tournament(&mut prng, &population, 100).value, 9);
+        }
+
+        // A tournament of one is a random pick.
+        let winners: Vec<i32> = (0..50)
+            .map(|_| tournament(&mut prng, &population, 1).value)
+            .collect();
+        assert!(winners.iter().any(|&value| value < 5));
+        assert!(winners.iter().any(|&value| value >= 5));
+    }
+
+    #[test]
+    fn mutation_rates_of_zero_and_one() {
+        let mut prng = Prng::new_with_seed(1);
+        let genes: Vec<bool> = (0..20).map(|i| i % 3 == 0).collect();
+
+        let mut unchanged = genes.clone();
+        mutate(&mut prng, &mut unchanged, 0.0);
+        assert_eq!(unchanged, genes);
+
+        let mut flipped = genes.clone();
+        mutate(&mut prng, &mut flipped, 1.0);
+        assert!(flipped.iter().zip(&genes).all(|(a, b)| a != b));
+    }
+
+    #[test]
+    fn one_point_crossover_cuts_once() {
+        let mut prng = Prng::new_with_seed(1);
+        let a = vec![true; 10];
+        let b = vec![false; 10];
+        for _ in 0..20 {
+            let child = crossover(&mut prng, &a, &b, Crossover::OnePoint);
+            let cut = child.iter().position(|&gene| !gene).unwrap();
+            assert!(cut >= 1);
+            assert!(child[..cut].iter().all(|&gene| gene));
+            assert!(child[cut..].iter().all(|&gene| !gene));
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// The greedy heuristic and the LP relaxation bound.
+// Both look at the items in order of decreasing value per weight.
+
+use crate::item::{check_instance, make_empty_selection, sum_values, Item, Solution};
+use crate::number::{Number, NumberError};
+
+// Return the item indices sorted by decreasing value/weight ratio.
+pub fn ratio_order<T: Number>(items: &[Item<T>]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| {
+        T::compare_ratios(
+            items[b].value,
+            items[b].weight,
+            items[a].value,
+            items[a].weight,
+        )
+    });
+    order
+}
+
+// Add items in ratio order while they fit, then compare the result
+// with the single most valuable item that fits and keep the better one.
+// Return the assignment, its value and the number of items considered.
+pub fn greedy<T: Number>(items: &mut [Item<T>], allowed_weight: T) -> Solution<T> {
+    try_greedy(items, allowed_weight).unwrap_or_else(|error| panic!("greedy: {}", error))
+}
+
+// Greedy that returns an error if the items' totals don't fit into T.
+pub fn try_greedy<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+) -> Result<Solution<T>, NumberError> {
+    check_instance(items, allowed_weight)?;
+    let mut solution = make_empty_selection(items);
+    let mut remaining_weight = allowed_weight;
+    for i in ratio_order(items) {
+        if items[i].weight <= remaining_weight {
+            solution[i].is_selected = true;
+            remaining_weight = remaining_weight - items[i].weight;
+        }
+    }
+
+    // The most valuable item that fits. Values are only PartialOrd,
+    // so this can't use max_by_key; ties go to the last item like there.
+    let best_single = (0..items.len())
+        .filter(|&i| items[i].weight <= allowed_weight)
+        .fold(None, |best: Option<usize>, i| match best {
+            Some(b) if items[b].value > items[i].value => Some(b),
+            _ => Some(i),
+        });
+    if let Some(i) = best_single {
+        if items[i].value > sum_values(&solution, false) {
+            solution = make_empty_selection(items);
+            solution[i].is_selected = true;
+        }
+    }
+
+    let value = sum_values(&solution, false);
+    Ok((solution, value, items.len() as i32))
+}
+
+// Return the value of the LP relaxation: take items in ratio order
+// and a fraction of the first item that doesn't fit.
+// No solution can be worth more than this.
+pub fn lp_bound<T: Number>(items: &[Item<T>], allowed_weight: T) -> f64 {
+    fractional_bound(items, &ratio_order(items), allowed_weight, None)
+}
+
+// Return the LP relaxation bound of the items in order, leaving out
+// the item at index skip if there is one.
+pub fn fractional_bound<T: Number>(
+    items: &[Item<T>],
+    order: &[usize],
+    allowed_weight: T,
+    skip: Option<usize>,
+) -> f64 {
+    let mut bound = 0.0;
+    let mut remaining_weight = allowed_weight;
+    for &i in order {
+        if Some(i) == skip {
+            continue;
+        }
+        if items[i].weight <= remaining_weight {
+            bound += items[i].value.as_f64();
+            remaining_weight = remaining_weight - items[i].weight;
+        } else {
+            bound += items[i].value.as_f64() * remaining_weight.as_f64() / items[i].weight.as_f64();
+            break;
+        }
+    }
+    bound
+}
+
+// Make an overweight selection fit: deselect the selected items in
+// reverse ratio order, worst first, until the weight is at most the
+// allowed weight. order is the ratio order of the items. value and
+// weight are the selection's; return those of the repaired selection.
+pub fn repair<T: Number>(
+    items: &[Item<T>],
+    selected: &mut [bool],
+    allowed_weight: T,
+    order: &[usize],
+    value: T,
+    weight: T,
+) -> (T, T) {
+    repair_recording(
+        items,
+        selected,
+        allowed_weight,
+        order,
+        value,
+        weight,
+        &mut vec![],
+    )
+}
+
+// Repair the selection and append the indices of the items it
+// deselects to dropped, so the caller can undo the repair.
+pub fn repair_recording<T: Number>(
+    items: &[Item<T>],
+    selected: &mut [bool],
+    allowed_weight: T,
+    order: &[usize],
+    value: T,
+    weight: T,
+    dropped: &mut Vec<usize>,
+) -> (T, T) {
+    let (mut value, mut weight) = (value, weight);
+    for &i in order.iter().rev() {
+        if weight <= allowed_weight {
+            break;
+        }
+        if selected[i] {
+            selected[i] = false;
+            value = value - items[i].value;
+            weight = weight - items[i].weight;
+            dropped.push(i);
+        }
+    }
+    (value, weight)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::{make_test_instance, sum_weights};
+
+    #[test]
+    fn repair_drops_the_worst_ratios() {
+        let (items, allowed_weight) = make_test_instance(3, 30);
+        let order = ratio_order(&items);
+        let mut selected = vec![true; items.len()];
+        let mut dropped = vec![];
+        let (value, weight) = repair_recording(
+            &items,
+            &mut selected,
+            allowed_weight,
+            &order,
+            sum_values(&items, true),
+            sum_weights(&items, true),
+            &mut dropped,
+        );
+        assert!(weight <= allowed_weight);
+        // The kept items are a prefix of the ratio order
+        // and the dropped ones the rest, worst first.
+        let kept = selected.iter().filter(|&&s| s).count();
+        assert!(order[..kept].iter().all(|&i| selected[i]));
+        let kept_value: i32 = order[..kept].iter().map(|&i| items[i].value).sum();
+        assert_eq!(value, kept_value);
+        assert!(dropped.iter().eq(order[kept..].iter().rev()));
+    }
+}
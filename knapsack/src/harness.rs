@@ -0,0 +1,119 @@
+use crate::item::{copy_items, print_selected, sum_weights, Item, Solution};
+use crate::limits::{Limits, SearchResult};
+use crate::number::Number;
+use crate::ordering::{with_order, ItemOrder};
+use crate::verify::{verify, Verified, Violation};
+use std::time::{Duration, Instant};
+
+// An algorithm takes the items and the allowed weight
+// and returns the best solution it could find.
+pub type Algorithm<T = i32> = dyn Fn(&mut [Item<T>], T) -> Solution<T>;
+
+// A search that stops when it hits one of the limits.
+pub type LimitedAlgorithm<T = i32> = dyn Fn(&mut [Item<T>], T, &Limits) -> SearchResult<Item<T>, T>;
+
+// Run f and return its result together with the elapsed time.
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+// Run the algorithm. Display the elapsed time and solution.
+pub fn run_algorithm<T: Number>(alg: &Algorithm<T>, items: &[Item<T>], allowed_weight: T) {
+    // Copy the items so the run isn't influenced by a previous run.
+    let mut test_items = copy_items(items);
+
+    // Run the algorithm.
+    let ((solution, total_value, function_calls), duration) =
+        timed(|| alg(&mut test_items, allowed_weight));
+
+    println!("Elapsed: {:?}", duration);
+
+    print_selected(&solution);
+    println!(
+        "Value: {}, Weight: {}, Calls: {}",
+        total_value,
+        sum_weights(&solution, false),
+        function_calls
+    );
+    print_verification(&verify(items, allowed_weight, &solution, total_value));
+    println!();
+}
+
+// Print what the verifier says about a solution.
+pub fn print_verification<T: Number>(verification: &Result<Verified<T>, Violation<T>>) {
+    match verification {
+        Ok(verified) => match verified.upper_bound {
+            Some(bound) if verified.proven_optimal => {
+                println!("Verified, proven optimal by the LP dual bound {}", bound)
+            }
+            Some(bound) => println!("Verified, LP dual bound: {}", bound),
+            None => println!("Verified"),
+        },
+        Err(violation) => println!("INVALID SOLUTION: {}", violation),
+    }
+}
+
+// Run the search with the given limits. Display the elapsed time,
+// the solution and, if a limit stopped the search, how far from
+// optimal the solution may be.
+pub fn run_limited<T: Number>(
+    alg: &LimitedAlgorithm<T>,
+    items: &[Item<T>],
+    allowed_weight: T,
+    limits: &Limits,
+) {
+    let mut test_items = copy_items(items);
+    let (result, duration) = timed(|| alg(&mut test_items, allowed_weight, limits));
+
+    println!("Elapsed: {:?}", duration);
+
+    print_selected(&result.items);
+    println!(
+        "Value: {}, Weight: {}, Calls: {}",
+        result.value,
+        sum_weights(&result.items, false),
+        result.calls
+    );
+    print_verification(&verify(items, allowed_weight, &result.items, result.value));
+    if !result.proven_optimal {
+        println!(
+            "Not proven optimal. Upper bound: {}, Gap: {:.2}%",
+            result.upper_bound,
+            100.0 * result.gap()
+        );
+    }
+    println!();
+}
+
+// Run the algorithm once for every item order and print a table
+// of the values, function calls and elapsed times.
+pub fn sweep_orders<T: Number>(
+    alg: &Algorithm<T>,
+    items: &[Item<T>],
+    allowed_weight: T,
+    orders: &[ItemOrder<T>],
+) {
+    println!(
+        "{:<14} {:>8} {:>12} {:>14}",
+        "Order", "Value", "Calls", "Elapsed"
+    );
+    for order in orders {
+        let ordered = with_order(alg, *order);
+        let mut test_items = copy_items(items);
+        let ((solution, total_value, function_calls), duration) =
+            timed(|| ordered(&mut test_items, allowed_weight));
+        if let Err(violation) = verify(items, allowed_weight, &solution, total_value) {
+            println!("INVALID SOLUTION for order {}: {}", order.name(), violation);
+        }
+        println!(
+            "{:<14} {:>8} {:>12} {:>14}",
+            order.name(),
+            total_value,
+            function_calls,
+            format!("{:?}", duration)
+        );
+    }
+    println!();
+}
@@ -0,0 +1,315 @@
+// The instance classes from Pisinger's "Where are the hard knapsack problems?".
+//
+// make_items draws values and weights independently, which is easy for
+// the tree searches. In the other classes the value follows the weight,
+// so the value/weight ratios are close together and bounds prune less.
+// R sets the magnitude of the weights and values.
+
+use crate::item::Item;
+use std::fmt;
+use std::str::FromStr;
+use utils::{Generator, Sampling};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstanceClass {
+    // w and p uniform in [1, R].
+    Uncorrelated,
+    // w in [1, R], p in [w - R/10, w + R/10] and at least 1.
+    WeaklyCorrelated,
+    // w in [1, R], p = w + R/10.
+    StronglyCorrelated,
+    // p in [1, R], w = p + R/10.
+    InverseStronglyCorrelated,
+    // w in [1, R], p in [w + R/10 - R/500, w + R/10 + R/500].
+    AlmostStronglyCorrelated,
+    // w in [1, R], p = w.
+    SubsetSum,
+    // w in [100R, 100R + R/10], p in [1, R].
+    UncorrelatedSimilarWeights,
+    // Multiples of a few small spanner items of the base class.
+    UncorrelatedSpanner,
+    WeaklyCorrelatedSpanner,
+    StronglyCorrelatedSpanner,
+    // w in [1, R], p = 3 * ceil(w / 3).
+    ProfitCeiling,
+    // w in [1, R], p = 2/3 * sqrt(4R^2 - (w - 2R)^2).
+    Circle,
+}
+
+// The largest range whose values and weights fit into an i32.
+// The similar weights class needs weights up to 100R + R/10.
+pub const MAX_RANGE: i32 = i32::MAX / 101;
+
+// Spanner instances use SPANNER_ITEMS spanner items
+// and multipliers in [1, SPANNER_MULTIPLIER].
+const SPANNER_ITEMS: usize = 2;
+const SPANNER_MULTIPLIER: i32 = 10;
+
+const CEILING: i32 = 3;
+
+impl InstanceClass {
+    pub fn all() -> [InstanceClass; 12] {
+        [
+            InstanceClass::Uncorrelated,
+            InstanceClass::WeaklyCorrelated,
+            InstanceClass::StronglyCorrelated,
+            InstanceClass::InverseStronglyCorrelated,
+            InstanceClass::AlmostStronglyCorrelated,
+            InstanceClass::SubsetSum,
+            InstanceClass::UncorrelatedSimilarWeights,
+            InstanceClass::UncorrelatedSpanner,
+            InstanceClass::WeaklyCorrelatedSpanner,
+            InstanceClass::StronglyCorrelatedSpanner,
+            InstanceClass::ProfitCeiling,
+            InstanceClass::Circle,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InstanceClass::Uncorrelated => "uncorrelated",
+            InstanceClass::WeaklyCorrelated => "weakly-correlated",
+            InstanceClass::StronglyCorrelated => "strongly-correlated",
+            InstanceClass::InverseStronglyCorrelated => "inverse-strongly-correlated",
+            InstanceClass::AlmostStronglyCorrelated => "almost-strongly-correlated",
+            InstanceClass::SubsetSum => "subset-sum",
+            InstanceClass::UncorrelatedSimilarWeights => "similar-weights",
+            InstanceClass::UncorrelatedSpanner => "uncorrelated-spanner",
+            InstanceClass::WeaklyCorrelatedSpanner => "weakly-correlated-spanner",
+            InstanceClass::StronglyCorrelatedSpanner => "strongly-correlated-spanner",
+            InstanceClass::ProfitCeiling => "profit-ceiling",
+            InstanceClass::Circle => "circle",
+        }
+    }
+
+    // Return one (value, weight) pair of this class.
+    fn make_pair(&self, prng: &mut dyn Generator, range: i32) -> (i32, i32) {
+        let tenth = range / 10;
+        match self {
+            InstanceClass::Uncorrelated => (prng.gen_range(1..=range), prng.gen_range(1..=range)),
+            InstanceClass::WeaklyCorrelated => {
+                let weight = prng.gen_range(1..=range);
+                let value = prng.gen_range(weight - tenth..=weight + tenth);
+                (value.max(1), weight)
+            }
+            InstanceClass::StronglyCorrelated => {
+                let weight = prng.gen_range(1..=range);
+                (weight + tenth, weight)
+            }
+            InstanceClass::InverseStronglyCorrelated => {
+                let value = prng.gen_range(1..=range);
+                (value, value + tenth)
+            }
+            InstanceClass::AlmostStronglyCorrelated => {
+                let weight = prng.gen_range(1..=range);
+                let spread = range / 500;
+                let value = prng.gen_range(weight + tenth - spread..=weight + tenth + spread);
+                (value, weight)
+            }
+            InstanceClass::SubsetSum => {
+                let weight = prng.gen_range(1..=range);
+                (weight, weight)
+            }
+            InstanceClass::UncorrelatedSimilarWeights => {
+                let weight = prng.gen_range(100 * range..=100 * range + tenth);
+                (prng.gen_range(1..=range), weight)
+            }
+            InstanceClass::UncorrelatedSpanner
+            | InstanceClass::WeaklyCorrelatedSpanner
+            | InstanceClass::StronglyCorrelatedSpanner => {
+                unreachable!("spanner items are made in make_instance")
+            }
+            InstanceClass::ProfitCeiling => {
+                let weight = prng.gen_range(1..=range);
+                let value = CEILING * ((weight + CEILING - 1) / CEILING);
+                (value, weight)
+            }
+            InstanceClass::Circle => {
+                let weight = prng.gen_range(1..=range);
+                let r = range as f64;
+                let w = weight as f64;
+                let value = 2.0 / 3.0 * (4.0 * r * r - (w - 2.0 * r).powi(2)).sqrt();
+                (value.round().max(1.0) as i32, weight)
+            }
+        }
+    }
+
+    // Return the class the spanner items of a spanner class are drawn from.
+    fn spanner_base(&self) -> Option<InstanceClass> {
+        match self {
+            InstanceClass::UncorrelatedSpanner => Some(InstanceClass::Uncorrelated),
+            InstanceClass::WeaklyCorrelatedSpanner => Some(InstanceClass::WeaklyCorrelated),
+            InstanceClass::StronglyCorrelatedSpanner => Some(InstanceClass::StronglyCorrelated),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for InstanceClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for InstanceClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        InstanceClass::all()
+            .into_iter()
+            .find(|class| class.name() == name)
+            .ok_or_else(|| format!("Unknown instance class: {s}"))
+    }
+}
+
+// Make num_items items of the given class with magnitude range.
+// range must be in [1, MAX_RANGE].
+pub fn make_instance(
+    prng: &mut dyn Generator,
+    class: InstanceClass,
+    num_items: i32,
+    range: i32,
+) -> Vec<Item> {
+    assert!(
+        (1..=MAX_RANGE).contains(&range),
+        "range must be in [1, {MAX_RANGE}]"
+    );
+
+    let pairs: Vec<(i32, i32)> = match class.spanner_base() {
+        None => (0..num_items)
+            .map(|_| class.make_pair(prng, range))
+            .collect(),
+        Some(base) => {
+            // Scale the spanner items down, then make every item
+            // a random multiple of a random spanner item.
+            let spanners: Vec<(i32, i32)> = (0..SPANNER_ITEMS)
+                .map(|_| {
+                    let (value, weight) = base.make_pair(prng, range);
+                    (
+                        (2 * value + SPANNER_MULTIPLIER - 1) / SPANNER_MULTIPLIER,
+                        (2 * weight + SPANNER_MULTIPLIER - 1) / SPANNER_MULTIPLIER,
+                    )
+                })
+                .collect();
+            (0..num_items)
+                .map(|_| {
+                    let &(value, weight) = prng.choose(&spanners).unwrap();
+                    let multiplier = prng.gen_range(1..=SPANNER_MULTIPLIER);
+                    (multiplier * value, multiplier * weight)
+                })
+                .collect()
+        }
+    };
+
+    pairs
+        .into_iter()
+        .map(|(value, weight)| Item {
+            value,
+            weight,
+            is_selected: false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::Xoshiro256;
+
+    const RANGE: i32 = 1000;
+
+    #[test]
+    fn classes_keep_their_relations() {
+        let mut prng = Xoshiro256::new_with_seed(1337);
+        for class in InstanceClass::all() {
+            let items = make_instance(&mut prng, class, 200, RANGE);
+            assert_eq!(items.len(), 200);
+            assert!(
+                items.iter().all(|item| item.value >= 1 && item.weight >= 1),
+                "{class}"
+            );
+
+            for item in items.iter() {
+                let (value, weight) = (item.value, item.weight);
+                match class {
+                    InstanceClass::StronglyCorrelated => assert_eq!(value, weight + 100),
+                    InstanceClass::InverseStronglyCorrelated => assert_eq!(weight, value + 100),
+                    InstanceClass::AlmostStronglyCorrelated => {
+                        assert!((value - weight - 100).abs() <= 2)
+                    }
+                    InstanceClass::WeaklyCorrelated => assert!((value - weight).abs() <= 100),
+                    InstanceClass::SubsetSum => assert_eq!(value, weight),
+                    InstanceClass::UncorrelatedSimilarWeights => {
+                        assert!((100_000..=100_100).contains(&weight))
+                    }
+                    InstanceClass::ProfitCeiling => {
+                        assert_eq!(value % 3, 0);
+                        assert!(value >= weight && value < weight + 3);
+                    }
+                    InstanceClass::Uncorrelated | InstanceClass::Circle => {
+                        assert!(weight <= RANGE)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn largest_range_fits() {
+        let mut prng = Xoshiro256::new_with_seed(1337);
+        for class in InstanceClass::all() {
+            let items = make_instance(&mut prng, class, 50, MAX_RANGE);
+            assert!(
+                items.iter().all(|item| item.value >= 1 && item.weight >= 1),
+                "{class}"
+            );
+        }
+    }
+
+    #[test]
+    fn spanner_items_are_multiples_of_few_items() {
+        let mut prng = Xoshiro256::new_with_seed(7);
+        let items = make_instance(
+            &mut prng,
+            InstanceClass::StronglyCorrelatedSpanner,
+            100,
+            RANGE,
+        );
+
+        // Reduce every item to its smallest multiple with the same ratio.
+        let mut bases: Vec<(i32, i32)> = items
+            .iter()
+            .map(|item| {
+                let (mut a, mut b) = (item.value, item.weight);
+                while b != 0 {
+                    (a, b) = (b, a % b);
+                }
+                (item.value / a, item.weight / a)
+            })
+            .collect();
+        bases.sort_unstable();
+        bases.dedup();
+        assert!(bases.len() <= SPANNER_ITEMS);
+    }
+
+    #[test]
+    fn instances_are_reproducible() {
+        for class in InstanceClass::all() {
+            let first = make_instance(&mut Xoshiro256::new_with_seed(3), class, 50, RANGE);
+            let second = make_instance(&mut Xoshiro256::new_with_seed(3), class, 50, RANGE);
+            let pairs = |items: &[Item]| -> Vec<(i32, i32)> {
+                items.iter().map(|item| (item.value, item.weight)).collect()
+            };
+            assert_eq!(pairs(&first), pairs(&second), "{class}");
+        }
+    }
+
+    #[test]
+    fn classes_parse() {
+        for class in InstanceClass::all() {
+            assert_eq!(class.name().parse::<InstanceClass>(), Ok(class));
+        }
+    }
+}
@@ -0,0 +1,451 @@
+// An interactive session for the knapsack programs.
+//
+// The session asks for the instance parameters and the algorithm,
+// shows the instance and runs the algorithm. Then the user can change
+// one parameter at a time and run again. Every run is compared with
+// the run of the same solver before it.
+
+use crate::harness::timed;
+use crate::item::{copy_items, sum_values, sum_weights, Item, Parameters};
+use crate::limits::Limits;
+use crate::solvers::{solvers, Solver};
+use crate::verify::verify;
+use std::env;
+use std::io::{BufRead, Write};
+use std::time::Duration;
+use utils::{InputError, Prompter};
+
+// Stop a tree search after this long so the session can't hang.
+const TIME_LIMIT: Duration = Duration::from_secs(10);
+
+const MAX_ITEMS: i32 = 10_000;
+const MAX_NUMBER: i32 = 1_000_000;
+
+// The dynamic program and the memoized search fill tables of
+// (items + 1) * (allowed weight + 1) cells. Keep them below this.
+const MAX_TABLE_CELLS: i64 = 50_000_000;
+
+// Only show the first SHOWN_ITEMS items of the instance.
+const SHOWN_ITEMS: usize = 20;
+
+const ALL_SOLVERS: &str = "all";
+
+const MENU: [&str; 7] = [
+    "Item count",
+    "Value range",
+    "Weight range",
+    "Seed",
+    "Algorithm",
+    "Run again",
+    "Quit",
+];
+
+// The outcome of one solver on one instance.
+struct Run {
+    solver: &'static str,
+    value: i32,
+    calls: i32,
+    elapsed: Duration,
+    // False if the search stopped at the time limit.
+    proven_optimal: bool,
+}
+
+// Return true if the program was started with --interactive or -i.
+pub fn is_interactive() -> bool {
+    env::args().any(|arg| arg == "--interactive" || arg == "-i")
+}
+
+// Run a session on the terminal, starting from the program's own
+// parameters and algorithm.
+pub fn interactive(defaults: Parameters, algorithm: &str) {
+    match run_session(&mut Prompter::stdio(), defaults, algorithm) {
+        Ok(()) | Err(InputError::Eof) => {}
+        Err(error) => eprintln!("{}", error),
+    }
+}
+
+pub fn run_session<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    defaults: Parameters,
+    algorithm: &str,
+) -> Result<(), InputError> {
+    writeln!(prompter.output(), "*** Interactive Mode ***")?;
+    writeln!(
+        prompter.output(),
+        "Press Enter to keep the value in brackets."
+    )?;
+    let mut parameters = defaults;
+    ask_num_items(prompter, &mut parameters)?;
+    ask_value_range(prompter, &mut parameters)?;
+    ask_weight_range(prompter, &mut parameters)?;
+    ask_seed(prompter, &mut parameters)?;
+    let mut algorithm = ask_algorithm(prompter, algorithm)?;
+
+    let mut previous: Vec<Run> = vec![];
+    loop {
+        let items = parameters.make_items();
+        show_instance(prompter.output(), &parameters, &items)?;
+        let runs = run_solvers(prompter.output(), &items, &algorithm)?;
+        show_runs(prompter.output(), &runs, &previous)?;
+
+        // Keep the latest run of every solver.
+        previous.retain(|old| runs.iter().all(|run| run.solver != old.solver));
+        previous.extend(runs);
+
+        match prompter.until_valid(|p| p.choose("Change: ", &MENU))? {
+            0 => ask_num_items(prompter, &mut parameters)?,
+            1 => ask_value_range(prompter, &mut parameters)?,
+            2 => ask_weight_range(prompter, &mut parameters)?,
+            3 => ask_seed(prompter, &mut parameters)?,
+            4 => algorithm = ask_algorithm(prompter, &algorithm)?,
+            5 => {}
+            _ => return Ok(()),
+        }
+    }
+}
+
+// The item count and the ranges limit each other: the totals must fit
+// into an i32 and the tables into MAX_TABLE_CELLS. Every answer is
+// checked against the other current parameters, so they always fit.
+
+// Return the number of table cells for num_items items of at most max_weight.
+fn table_cells(num_items: i32, max_weight: i32) -> i64 {
+    let allowed_weight = num_items as i64 * max_weight as i64 / 2;
+    (num_items as i64 + 1) * (allowed_weight + 1)
+}
+
+fn max_items(parameters: &Parameters) -> i32 {
+    let mut num_items = MAX_ITEMS.min(i32::MAX / parameters.max_value.max(1));
+    while num_items > 1 && table_cells(num_items, parameters.max_weight) > MAX_TABLE_CELLS {
+        num_items -= 1;
+    }
+    num_items
+}
+
+fn max_value(parameters: &Parameters) -> i32 {
+    MAX_NUMBER.min(i32::MAX / parameters.num_items)
+}
+
+fn max_weight(parameters: &Parameters) -> i32 {
+    let num_items = parameters.num_items as i64;
+    let allowed_weight = MAX_TABLE_CELLS / (num_items + 1) - 1;
+    let weight = (2 * allowed_weight + 1) / num_items;
+    MAX_NUMBER.min(weight as i32)
+}
+
+fn ask_num_items<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    parameters: &mut Parameters,
+) -> Result<(), InputError> {
+    let prompt = format!("# items [{}]: ", parameters.num_items);
+    let limit = max_items(parameters);
+    parameters.num_items =
+        prompter.until_valid(|p| p.get_in_range_or(&prompt, 1, limit, parameters.num_items))?;
+    Ok(())
+}
+
+// Ask for a range min..max with max at most limit.
+// The generators need min < max.
+fn ask_range<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    name: &str,
+    min: &mut i32,
+    max: &mut i32,
+    limit: i32,
+) -> Result<(), InputError> {
+    let prompt = format!("Min {} [{}]: ", name, *min);
+    let current = *min;
+    *min = prompter.until_valid(|p| p.get_in_range_or(&prompt, 0, limit - 1, current))?;
+
+    let prompt = format!("Max {} [{}]: ", name, *max);
+    let current = (*max).max(*min + 1);
+    *max = prompter.until_valid(|p| p.get_in_range_or(&prompt, *min + 1, limit, current))?;
+    Ok(())
+}
+
+fn ask_value_range<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    parameters: &mut Parameters,
+) -> Result<(), InputError> {
+    let limit = max_value(parameters);
+    ask_range(
+        prompter,
+        "value",
+        &mut parameters.min_value,
+        &mut parameters.max_value,
+        limit,
+    )
+}
+
+fn ask_weight_range<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    parameters: &mut Parameters,
+) -> Result<(), InputError> {
+    let limit = max_weight(parameters);
+    ask_range(
+        prompter,
+        "weight",
+        &mut parameters.min_weight,
+        &mut parameters.max_weight,
+        limit,
+    )
+}
+
+fn ask_seed<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    parameters: &mut Parameters,
+) -> Result<(), InputError> {
+    let prompt = format!("Seed [{}]: ", parameters.seed);
+    parameters.seed =
+        prompter.until_valid(|p| p.get_in_range_or(&prompt, 0, u32::MAX, parameters.seed))?;
+    Ok(())
+}
+
+// Ask for a solver name, or "all" for every solver.
+fn ask_algorithm<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    current: &str,
+) -> Result<String, InputError> {
+    let mut choices: Vec<&str> = solvers().iter().map(|solver| solver.name).collect();
+    choices.push(ALL_SOLVERS);
+    let prompt = format!("Algorithm [{}]: ", current);
+    prompter.until_valid(|p| match p.choose(&prompt, &choices) {
+        Err(InputError::InvalidChoice(line)) if line.is_empty() => Ok(current.to_string()),
+        choice => choice.map(|i| choices[i].to_string()),
+    })
+}
+
+fn show_instance(
+    output: &mut impl Write,
+    parameters: &Parameters,
+    items: &[Item],
+) -> Result<(), InputError> {
+    writeln!(output)?;
+    writeln!(output, "*** Instance ***")?;
+    writeln!(output, "# items:        {}", parameters.num_items)?;
+    writeln!(
+        output,
+        "Values:         {}..{}",
+        parameters.min_value, parameters.max_value
+    )?;
+    writeln!(
+        output,
+        "Weights:        {}..{}",
+        parameters.min_weight, parameters.max_weight
+    )?;
+    writeln!(output, "Seed:           {}", parameters.seed)?;
+    writeln!(output, "Total value:    {}", sum_values(items, true))?;
+    writeln!(output, "Total weight:   {}", sum_weights(items, true))?;
+    writeln!(output, "Allowed weight: {}", sum_weights(items, true) / 2)?;
+    for (i, item) in items.iter().take(SHOWN_ITEMS).enumerate() {
+        write!(output, "{}({}, {}) ", i, item.value, item.weight)?;
+    }
+    if items.len() > SHOWN_ITEMS {
+        write!(output, "...")?;
+    }
+    writeln!(output)?;
+    writeln!(output)?;
+    Ok(())
+}
+
+// Run the chosen solvers. Tree searches stop at the time limit.
+fn run_solvers(
+    output: &mut impl Write,
+    items: &[Item],
+    algorithm: &str,
+) -> Result<Vec<Run>, InputError> {
+    let allowed_weight = sum_weights(items, true) / 2;
+    let chosen: Vec<Solver> = solvers()
+        .into_iter()
+        .filter(|solver| algorithm == ALL_SOLVERS || solver.name == algorithm)
+        .collect();
+
+    let mut runs = vec![];
+    for solver in chosen {
+        let mut test_items = copy_items(items);
+        let ((solution, value, calls, proven_optimal), elapsed) =
+            timed(|| match solver.solve_limited {
+                Some(solve_limited) => {
+                    let limits = Limits::none().with_time(TIME_LIMIT);
+                    let result = solve_limited(&mut test_items, allowed_weight, &limits);
+                    let proven_optimal = result.proven_optimal;
+                    let (solution, value, calls) = result.into_solution();
+                    (solution, value, calls, proven_optimal)
+                }
+                None => {
+                    let (solution, value, calls) = (solver.solve)(&mut test_items, allowed_weight);
+                    (solution, value, calls, true)
+                }
+            });
+        if let Err(violation) = verify(items, allowed_weight, &solution, value) {
+            writeln!(
+                output,
+                "INVALID SOLUTION from {}: {}",
+                solver.name, violation
+            )?;
+        }
+        runs.push(Run {
+            solver: solver.name,
+            value,
+            calls,
+            elapsed,
+            proven_optimal,
+        });
+    }
+    Ok(runs)
+}
+
+// Print the runs next to the previous run of each solver.
+fn show_runs(output: &mut impl Write, runs: &[Run], previous: &[Run]) -> Result<(), InputError> {
+    writeln!(
+        output,
+        "{:<20} {:>8} {:>12} {:>14} {:>14} {:>8}",
+        "Solver", "Value", "Calls", "Elapsed", "Previous", "Change"
+    )?;
+    for run in runs {
+        let value = match run.proven_optimal {
+            true => run.value.to_string(),
+            false => format!("{}*", run.value),
+        };
+        let old = previous.iter().find(|old| old.solver == run.solver);
+        let (old_elapsed, change) = match old {
+            Some(old) => (
+                format!("{:?}", old.elapsed),
+                format_change(old.elapsed, run.elapsed),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        writeln!(
+            output,
+            "{:<20} {:>8} {:>12} {:>14} {:>14} {:>8}",
+            run.solver,
+            value,
+            run.calls,
+            format!("{:?}", run.elapsed),
+            old_elapsed,
+            change
+        )?;
+    }
+    if runs.iter().any(|run| !run.proven_optimal) {
+        writeln!(output, "* stopped at the time limit of {:?}", TIME_LIMIT)?;
+    }
+    writeln!(output)?;
+    Ok(())
+}
+
+// Format the relative change from old to new, like "+12.5%".
+fn format_change(old: Duration, new: Duration) -> String {
+    if old.is_zero() {
+        return "-".to_string();
+    }
+    let change = (new.as_secs_f64() - old.as_secs_f64()) / old.as_secs_f64();
+    format!("{:+.1}%", 100.0 * change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::make_items;
+    use std::io::Cursor;
+    use utils::Prng;
+
+    const DEFAULTS: Parameters = Parameters {
+        num_items: 10,
+        min_value: 1,
+        max_value: 10,
+        min_weight: 4,
+        max_weight: 10,
+        seed: 1337,
+    };
+
+    fn run_script(script: &str) -> (Result<(), InputError>, String) {
+        let mut prompter = Prompter::new(Cursor::new(script.as_bytes()), Vec::new());
+        let result = run_session(&mut prompter, DEFAULTS, "greedy");
+        let output = String::from_utf8(prompter.output().clone()).unwrap();
+        (result, output)
+    }
+
+    #[test]
+    fn defaults_reproduce_the_program_items() {
+        let mut prng = Prng::new_with_seed(1337);
+        let expected = make_items(&mut prng, 10, 1, 10, 4, 10);
+        let items = DEFAULTS.make_items();
+        assert!(items
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| a.value == b.value && a.weight == b.weight));
+    }
+
+    #[test]
+    fn session_reruns_and_compares() {
+        // Keep every default, then change the seed, switch
+        // to branch and bound, run again and quit.
+        let script = "\n\n\n\n\n\n\n4\n7\n5\nbranch-and-bound\n6\n7\n";
+        let (result, output) = run_script(script);
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(output.matches("*** Instance ***").count(), 4);
+        assert!(output.contains("Seed:           7"));
+
+        // The second greedy run has a previous run, the first
+        // branch and bound run doesn't, the second one does.
+        let rows: Vec<&str> = output
+            .lines()
+            .filter(|line| line.starts_with("greedy") || line.starts_with("branch-and-bound"))
+            .collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows[0].ends_with('-'));
+        assert!(rows[1].ends_with('%'));
+        assert!(rows[2].ends_with('-'));
+        assert!(rows[3].ends_with('%'));
+        assert!(!output.contains("INVALID"));
+    }
+
+    #[test]
+    fn invalid_answers_are_asked_again() {
+        // A max below the min is out of range and asked again.
+        let script = "5\n3\n2\n20\n\n\n\n\n7\n";
+        let (result, output) = run_script(script);
+        assert!(result.is_ok(), "{:?}", result);
+        assert!(output.contains("Please try again"));
+        assert!(output.contains("Values:         3..20"));
+    }
+
+    #[test]
+    fn instances_stay_within_the_limits() {
+        // 10,000 items of weight up to 10 need too big a table,
+        // so the count is asked again. Then the weights are too big.
+        let script = "10000
+3000
+
+
+
+1000000
+10
+
+
+7
+";
+        let (result, output) = run_script(script);
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(output.matches("Please try again").count(), 2);
+        assert!(output.contains("# items:        3000"));
+
+        for num_items in [1, 10, 100, 3000, MAX_ITEMS] {
+            let parameters = Parameters {
+                num_items,
+                max_value: MAX_NUMBER,
+                max_weight: 1,
+                ..DEFAULTS
+            };
+            assert!(table_cells(num_items, max_weight(&parameters)) <= MAX_TABLE_CELLS);
+            assert!(num_items as i64 * max_value(&parameters) as i64 <= i32::MAX as i64);
+        }
+        assert!(table_cells(max_items(&DEFAULTS), DEFAULTS.max_weight) <= MAX_TABLE_CELLS);
+    }
+
+    #[test]
+    fn the_end_of_the_input_ends_the_session() {
+        let (result, _) = run_script("5\n");
+        assert!(matches!(result, Err(InputError::Eof)));
+    }
+}
@@ -0,0 +1,192 @@
+use crate::number::{checked_total, Number, NumberError};
+use std::cmp::Ordering;
+use utils::{Generator, Prng};
+
+// Values and weights are i32 unless a program asks for another Number.
+pub struct Item<T = i32> {
+    pub value: T,
+    pub weight: T,
+    pub is_selected: bool,
+}
+
+// The result of an algorithm: the best assignment,
+// the value of that assignment and the number of
+// function calls (or steps) the algorithm needed.
+pub type Solution<T = i32> = (Vec<Item<T>>, T, i32);
+
+// Make some random items.
+pub fn make_items(
+    prng: &mut dyn Generator,
+    num_items: i32,
+    min_value: i32,
+    max_value: i32,
+    min_weight: i32,
+    max_weight: i32,
+) -> Vec<Item> {
+    let mut items: Vec<Item> = Vec::with_capacity(num_items as usize);
+    for _ in 0..num_items {
+        let item = Item {
+            value: prng.next_i32(min_value, max_value),
+            weight: prng.next_i32(min_weight, max_weight),
+            is_selected: false,
+        };
+        items.push(item);
+    }
+    items
+}
+
+// Return an unselected item, for tests.
+#[cfg(test)]
+pub(crate) fn make_item<T>(value: T, weight: T) -> Item<T> {
+    Item {
+        value,
+        weight,
+        is_selected: false,
+    }
+}
+
+// Return num_items items with values and weights up to 100
+// and half their total weight as the allowed weight, for tests.
+#[cfg(test)]
+pub(crate) fn make_test_instance(seed: u32, num_items: i32) -> (Vec<Item>, i32) {
+    let mut prng = Prng::new_with_seed(seed);
+    let items = make_items(&mut prng, num_items, 1, 100, 4, 100);
+    let allowed_weight = sum_weights(&items, true) / 2;
+    (items, allowed_weight)
+}
+
+// The parameters the items are made from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Parameters {
+    pub num_items: i32,
+    pub min_value: i32,
+    pub max_value: i32,
+    pub min_weight: i32,
+    pub max_weight: i32,
+    pub seed: u32,
+}
+
+impl Parameters {
+    pub fn make_items(&self) -> Vec<Item> {
+        let mut prng = Prng::new_with_seed(self.seed);
+        make_items(
+            &mut prng,
+            self.num_items,
+            self.min_value,
+            self.max_value,
+            self.min_weight,
+            self.max_weight,
+        )
+    }
+}
+
+// Return a copy of the items.
+pub fn copy_items<T: Copy>(items: &[Item<T>]) -> Vec<Item<T>> {
+    let mut new_items: Vec<Item<T>> = Vec::with_capacity(items.len());
+    for item in items {
+        let new_item = Item {
+            value: item.value,
+            weight: item.weight,
+            is_selected: item.is_selected,
+        };
+        new_items.push(new_item);
+    }
+    new_items
+}
+
+// Return a copy of the items with nothing selected.
+pub fn make_empty_selection<T: Copy>(items: &[Item<T>]) -> Vec<Item<T>> {
+    let mut new_items = copy_items(items);
+    for item in new_items.iter_mut() {
+        item.is_selected = false;
+    }
+    new_items
+}
+
+// Return the total value of the items.
+// If add_all is true, add up all items.
+// If add_all is false, only add up the selected items.
+// Panic if the total doesn't fit; checked_sum_values returns an error.
+pub fn sum_values<T: Number>(items: &[Item<T>], add_all: bool) -> T {
+    checked_sum_values(items, add_all).expect("Sum of values overflows")
+}
+
+// Return the total weight of the items.
+// If add_all is false, only add up the selected items.
+// If add_all is true, add up all items.
+// Panic if the total doesn't fit; checked_sum_weights returns an error.
+pub fn sum_weights<T: Number>(items: &[Item<T>], add_all: bool) -> T {
+    checked_sum_weights(items, add_all).expect("Sum of weights overflows")
+}
+
+pub fn checked_sum_values<T: Number>(items: &[Item<T>], add_all: bool) -> Result<T, NumberError> {
+    checked_total(
+        items
+            .iter()
+            .filter(|item| add_all || item.is_selected)
+            .map(|item| item.value),
+    )
+}
+
+pub fn checked_sum_weights<T: Number>(items: &[Item<T>], add_all: bool) -> Result<T, NumberError> {
+    checked_total(
+        items
+            .iter()
+            .filter(|item| add_all || item.is_selected)
+            .map(|item| item.weight),
+    )
+}
+
+// Check that the values, weights and allowed weight are non-negative
+// and that the totals fit, so no subset of the items can overflow.
+pub fn check_instance<T: Number>(items: &[Item<T>], allowed_weight: T) -> Result<(), NumberError> {
+    // NaN counts as negative: it compares neither less nor greater.
+    let negative = |number: T| {
+        !matches!(
+            number.partial_cmp(&T::zero()),
+            Some(Ordering::Greater | Ordering::Equal)
+        )
+    };
+    if negative(allowed_weight)
+        || items
+            .iter()
+            .any(|item| negative(item.value) || negative(item.weight))
+    {
+        return Err(NumberError::Negative);
+    }
+    checked_sum_values(items, true)?;
+    checked_sum_weights(items, true)?;
+    Ok(())
+}
+
+// Return the value of this solution.
+// If the solution is too heavy, return -1 so we prefer an empty solution.
+pub fn solution_value(items: &[Item], allowed_weight: i32) -> i32 {
+    feasible_value(items, allowed_weight).unwrap_or(-1)
+}
+
+// Return the sum of the selected values, or None if the solution is
+// too heavy. None compares less than every value, so even an empty
+// solution is better. This works for unsigned values, unlike -1.
+pub fn feasible_value<T: Number>(items: &[Item<T>], allowed_weight: T) -> Option<T> {
+    if sum_weights(items, false) > allowed_weight {
+        return None;
+    }
+    Some(sum_values(items, false))
+}
+
+// Print the selected items.
+pub fn print_selected<T: Number>(items: &[Item<T>]) {
+    let mut num_printed = 0;
+    for (i, item) in items.iter().enumerate() {
+        if item.is_selected {
+            print!("{}({}, {}) ", i, item.value, item.weight)
+        }
+        num_printed += 1;
+        if num_printed > 100 {
+            println!("...");
+            return;
+        }
+    }
+    println!();
+}
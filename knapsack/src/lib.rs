@@ -0,0 +1,31 @@
+// Shared building blocks for the knapsack programs:
+// the item model, the instance generators, the harness
+// that runs and reports an algorithm, and the solvers
+// that more than one program needs.
+
+pub mod annealing;
+pub mod branch_bound;
+pub mod change;
+pub mod dominance;
+pub mod dp;
+pub mod dp_render;
+pub mod exhaustive;
+pub mod genetic;
+pub mod greedy;
+pub mod harness;
+pub mod instances;
+pub mod interactive;
+pub mod item;
+pub mod limits;
+pub mod memo;
+pub mod number;
+pub mod ordering;
+pub mod output;
+pub mod preprocess;
+pub mod progress;
+pub mod real;
+pub mod rods;
+pub mod solvers;
+pub mod subset_sum;
+pub mod trace;
+pub mod verify;
@@ -0,0 +1,469 @@
+// Time and node limits for the tree searches.
+//
+// A search counts its nodes in a Budget. Once a limit is hit, every node
+// the search still reaches returns at once and leaves behind an upper
+// bound on the value in its subtree. The best of these bounds and the
+// incumbent's value bound the optimum, so a stopped search can still
+// say how far its incumbent may be from optimal.
+//
+// Setting the cancel flag stops a search the same way a limit does.
+//
+// With a Trace, the Budget also records the search tree.
+
+use crate::item::{Item, Solution};
+use crate::number::Number;
+use crate::progress::{Observer, Progress};
+use crate::trace::{Decision, Prune, Trace, TraceNode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Only look at the clock, the cancel flag and the
+// progress interval every CLOCK_INTERVAL nodes.
+const CLOCK_INTERVAL: i32 = 1024;
+
+#[derive(Clone, Default)]
+pub struct Limits {
+    pub time: Option<Duration>,
+    pub nodes: Option<i32>,
+    // Stop the search once this is set.
+    pub cancel: Option<Arc<AtomicBool>>,
+    pub observer: Option<Arc<dyn Observer + Send + Sync>>,
+    // How often the observer gets a progress report.
+    pub progress_interval: Duration,
+    // Record the search tree into this trace.
+    pub trace: Option<Arc<Mutex<Trace>>>,
+}
+
+impl Limits {
+    // No limits: search until done.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn with_time(self, time: Duration) -> Self {
+        Self {
+            time: Some(time),
+            ..self
+        }
+    }
+
+    pub fn with_nodes(self, nodes: i32) -> Self {
+        Self {
+            nodes: Some(nodes),
+            ..self
+        }
+    }
+
+    pub fn with_cancel(self, cancel: Arc<AtomicBool>) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+
+    pub fn with_observer(
+        self,
+        observer: Arc<dyn Observer + Send + Sync>,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            observer: Some(observer),
+            progress_interval: interval,
+            ..self
+        }
+    }
+
+    pub fn with_trace(self, trace: Arc<Mutex<Trace>>) -> Self {
+        Self {
+            trace: Some(trace),
+            ..self
+        }
+    }
+}
+
+// V is the type of the values the search adds up.
+pub struct Budget<V = i32> {
+    start: Instant,
+    deadline: Option<Instant>,
+    max_nodes: Option<i32>,
+    cancel: Option<Arc<AtomicBool>>,
+    observer: Option<Arc<dyn Observer + Send + Sync>>,
+    progress_interval: Duration,
+    next_progress: Instant,
+    nodes: i32,
+    stopped: bool,
+    // The best bound of the subtrees that were left unexplored.
+    open_bound: Option<V>,
+    // These are only kept for the observer.
+    incumbent: Option<V>,
+    root_bound: Option<V>,
+    depth_counts: Vec<i32>,
+    trace: Option<Arc<Mutex<Trace>>>,
+}
+
+impl<V: Number> Budget<V> {
+    pub fn new(limits: &Limits) -> Self {
+        let start = Instant::now();
+        Self {
+            start,
+            deadline: limits.time.map(|time| start + time),
+            max_nodes: limits.nodes,
+            cancel: limits.cancel.clone(),
+            observer: limits.observer.clone(),
+            progress_interval: limits.progress_interval,
+            next_progress: start + limits.progress_interval,
+            nodes: 0,
+            stopped: false,
+            open_bound: None,
+            incumbent: None,
+            root_bound: None,
+            depth_counts: vec![],
+            trace: limits.trace.clone(),
+        }
+    }
+
+    // Count a node at the given depth. Return false if a limit is hit and
+    // the node must not be explored. bound returns an upper bound on the
+    // value in the node's subtree; it is only called for nodes that aren't
+    // explored, and for the root if there is an observer.
+    pub fn visit(&mut self, depth: usize, bound: impl FnOnce() -> V) -> bool {
+        if !self.stopped && self.limit_reached() {
+            self.stopped = true;
+        }
+        let is_root = depth == 0 && self.observer.is_some();
+        if self.stopped || is_root {
+            let bound = bound();
+            if is_root {
+                self.root_bound = Some(bound);
+            }
+            if self.stopped {
+                self.open_bound = Some(self.open_bound.map_or(bound, |open| larger(open, bound)));
+                return false;
+            }
+        }
+        self.nodes += 1;
+        if self.observer.is_some() {
+            if self.depth_counts.len() <= depth {
+                self.depth_counts.resize(depth + 1, 0);
+            }
+            self.depth_counts[depth] += 1;
+        }
+        true
+    }
+
+    // Tell the budget about a solution the search found. The observer
+    // hears about it if it is better than every solution before it.
+    pub fn found(&mut self, value: V) {
+        if self.incumbent.is_some_and(|incumbent| incumbent >= value) {
+            return;
+        }
+        self.incumbent = Some(value);
+        if let Some(observer) = &self.observer {
+            observer.on_incumbent(value.as_f64(), self.nodes);
+        }
+    }
+
+    // Record a node the search visits, if there is a trace.
+    // improved says if the node's selection beats the best so far.
+    pub fn trace_visit(
+        &self,
+        depth: usize,
+        decision: Decision,
+        value: V,
+        weight: V,
+        bound: V,
+        improved: bool,
+    ) {
+        if let Some(trace) = &self.trace {
+            trace.lock().unwrap().visit(TraceNode {
+                parent: None,
+                depth,
+                decision,
+                value: value.as_f64(),
+                weight: weight.as_f64(),
+                bound: bound.as_f64(),
+                visited: true,
+                pruned: None,
+                improved,
+            });
+        }
+    }
+
+    // Record a child at depth that the search doesn't visit.
+    pub fn trace_pruned_child(
+        &self,
+        depth: usize,
+        decision: Decision,
+        value: V,
+        weight: V,
+        bound: V,
+        reason: Prune,
+    ) {
+        if let Some(trace) = &self.trace {
+            trace.lock().unwrap().prune_child(TraceNode {
+                parent: None,
+                depth,
+                decision,
+                value: value.as_f64(),
+                weight: weight.as_f64(),
+                bound: bound.as_f64(),
+                visited: false,
+                pruned: Some(reason),
+                improved: false,
+            });
+        }
+    }
+
+    // Record that the search returns from the node at depth early.
+    pub fn trace_prune(&self, depth: usize, reason: Prune) {
+        if let Some(trace) = &self.trace {
+            trace.lock().unwrap().prune(depth, reason);
+        }
+    }
+
+    fn limit_reached(&mut self) -> bool {
+        if self
+            .max_nodes
+            .is_some_and(|max_nodes| self.nodes >= max_nodes)
+        {
+            return true;
+        }
+        if self.nodes % CLOCK_INTERVAL != 0 {
+            return false;
+        }
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+        {
+            return true;
+        }
+        if self.deadline.is_none() && self.observer.is_none() {
+            return false;
+        }
+        let now = Instant::now();
+        if self.observer.is_some() && now >= self.next_progress {
+            self.next_progress = now + self.progress_interval;
+            self.report(|observer, progress| observer.on_progress(progress));
+        }
+        self.deadline.is_some_and(|deadline| now >= deadline)
+    }
+
+    fn report(&self, event: impl FnOnce(&dyn Observer, &Progress)) {
+        if let Some(observer) = &self.observer {
+            let progress = Progress {
+                nodes: self.nodes,
+                elapsed: self.start.elapsed(),
+                incumbent: self.incumbent.map(V::as_f64),
+                bound: self.root_bound.map(V::as_f64),
+                depth_counts: &self.depth_counts,
+            };
+            event(observer.as_ref(), &progress);
+        }
+    }
+
+    pub fn nodes(&self) -> i32 {
+        self.nodes
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    // Turn the incumbent into the result of the search.
+    pub fn finish<T>(&self, items: Vec<T>, value: V) -> SearchResult<T, V> {
+        self.report(|observer, progress| observer.on_finish(progress));
+        let upper_bound = match self.open_bound {
+            Some(open) if self.stopped => larger(open, value),
+            _ => value,
+        };
+        SearchResult {
+            items,
+            value,
+            calls: self.nodes,
+            upper_bound,
+            proven_optimal: upper_bound == value,
+        }
+    }
+}
+
+// The result of a search that may have been stopped by a limit.
+// Programs with their own item type can use it too.
+pub struct SearchResult<T = Item, V = i32> {
+    pub items: Vec<T>,
+    pub value: V,
+    pub calls: i32,
+    // No solution is worth more than this.
+    pub upper_bound: V,
+    pub proven_optimal: bool,
+}
+
+impl<T, V: Number> SearchResult<T, V> {
+    // Return how much better than value the optimum may be,
+    // relative to the upper bound.
+    pub fn gap(&self) -> f64 {
+        if self.upper_bound <= V::zero() {
+            return 0.0;
+        }
+        (self.upper_bound - self.value).as_f64() / self.upper_bound.as_f64()
+    }
+}
+
+impl<V> SearchResult<Item<V>, V> {
+    pub fn into_solution(self) -> Solution<V> {
+        (self.items, self.value, self.calls)
+    }
+}
+
+// Return the larger of two values that are only PartialOrd.
+fn larger<V: PartialOrd>(a: V, b: V) -> V {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::branch_bound::branch_and_bound_limited;
+    use crate::dp::dynamic_programming;
+    use crate::exhaustive::exhaustive_search_limited;
+    use crate::item::{copy_items, make_items, sum_weights};
+    use crate::rods::{rods_technique_limited, rods_technique_sorted_limited};
+    use crate::verify::verify;
+    use std::sync::Mutex;
+    use utils::Prng;
+
+    // The nodes, root bound and depth counts of the final report.
+    type FinalReport = (i32, Option<f64>, Vec<i32>);
+
+    // Remember every event the search reports.
+    #[derive(Default)]
+    struct Recorder {
+        incumbents: Mutex<Vec<f64>>,
+        finished: Mutex<Option<FinalReport>>,
+    }
+
+    impl Observer for Recorder {
+        fn on_incumbent(&self, value: f64, _nodes: i32) {
+            self.incumbents.lock().unwrap().push(value);
+        }
+
+        fn on_finish(&self, progress: &Progress) {
+            *self.finished.lock().unwrap() = Some((
+                progress.nodes,
+                progress.bound,
+                progress.depth_counts.to_vec(),
+            ));
+        }
+    }
+
+    type LimitedSearch = fn(&mut [Item], i32, &Limits) -> SearchResult;
+
+    #[test]
+    fn unlimited_budget_never_stops() {
+        let mut budget = Budget::new(&Limits::none());
+        assert!((0..10_000).all(|_| budget.visit(0, || 5)));
+        let result = budget.finish::<Item>(vec![], 3);
+        assert!(result.proven_optimal);
+        assert_eq!(result.upper_bound, 3);
+        assert_eq!(result.calls, 10_000);
+    }
+
+    #[test]
+    fn node_limit_stops_and_keeps_bounds() {
+        let mut budget = Budget::new(&Limits::none().with_nodes(3));
+        assert!(budget.visit(0, || 100));
+        assert!(budget.visit(0, || 100));
+        assert!(budget.visit(0, || 100));
+        assert!(!budget.visit(0, || 12));
+        assert!(!budget.visit(0, || 9));
+        assert!(budget.is_stopped());
+
+        let result = budget.finish::<Item>(vec![], 8);
+        assert_eq!(result.calls, 3);
+        assert_eq!(result.upper_bound, 12);
+        assert!(!result.proven_optimal);
+        assert!((result.gap() - 4.0 / 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn time_limit_stops() {
+        let mut budget = Budget::new(&Limits::none().with_time(Duration::ZERO));
+        assert!(!budget.visit(0, || 1));
+    }
+
+    #[test]
+    fn stopped_searches_bound_the_optimum() {
+        let searches: [LimitedSearch; 4] = [
+            exhaustive_search_limited,
+            branch_and_bound_limited,
+            rods_technique_limited,
+            rods_technique_sorted_limited,
+        ];
+        let mut prng = Prng::new_with_seed(1337);
+        let items = make_items(&mut prng, 25, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        let (_, optimum, _) = dynamic_programming(&mut copy_items(&items), allowed_weight);
+
+        for search in searches {
+            for nodes in [1, 10, 1000] {
+                let limits = Limits::none().with_nodes(nodes);
+                let result = search(&mut copy_items(&items), allowed_weight, &limits);
+                assert!(result.calls <= nodes);
+                verify(&items, allowed_weight, &result.items, result.value).unwrap();
+                assert!(result.value <= optimum && optimum <= result.upper_bound);
+                assert_eq!(result.proven_optimal, result.value == result.upper_bound);
+            }
+        }
+    }
+
+    #[test]
+    fn observer_hears_incumbents_and_depths() {
+        let searches: [LimitedSearch; 4] = [
+            exhaustive_search_limited,
+            branch_and_bound_limited,
+            rods_technique_limited,
+            rods_technique_sorted_limited,
+        ];
+        let mut prng = Prng::new_with_seed(1337);
+        let items = make_items(&mut prng, 12, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+
+        for search in searches {
+            let recorder = Arc::new(Recorder::default());
+            let limits = Limits::none().with_observer(recorder.clone(), Duration::ZERO);
+            let result = search(&mut copy_items(&items), allowed_weight, &limits);
+
+            // Every incumbent beats the one before it and the last is the result.
+            let incumbents = recorder.incumbents.lock().unwrap();
+            assert!(incumbents.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(incumbents.last(), Some(&(result.value as f64)));
+
+            let (nodes, bound, depth_counts) = recorder.finished.lock().unwrap().take().unwrap();
+            assert_eq!(nodes, result.calls);
+            assert_eq!(depth_counts.iter().sum::<i32>(), result.calls);
+            assert_eq!(depth_counts[0], 1);
+            assert!(depth_counts.len() <= items.len() + 1);
+            assert!(bound.is_some_and(|bound| bound >= result.value as f64));
+        }
+    }
+
+    #[test]
+    fn cancelled_search_stops_with_a_bound() {
+        let mut prng = Prng::new_with_seed(1337);
+        let items = make_items(&mut prng, 25, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        let (_, optimum, _) = dynamic_programming(&mut copy_items(&items), allowed_weight);
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        let limits = Limits::none().with_cancel(cancel);
+        let result = exhaustive_search_limited(&mut copy_items(&items), allowed_weight, &limits);
+        assert_eq!(result.calls, 0);
+        assert!(!result.proven_optimal);
+        assert!(result.value <= optimum && optimum <= result.upper_bound);
+    }
+}
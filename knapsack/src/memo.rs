@@ -0,0 +1,449 @@
+// Top-down dynamic programming: the exhaustive search with a memo.
+//
+// The best value of the items from next_index on only depends on
+// next_index and the remaining capacity, not on how the search got
+// there. Once the memo knows that value, the search doesn't explore
+// the subtree again. That turns the 2^n nodes of the exhaustive search
+// into at most n * (capacity + 1) distinct subproblems.
+//
+// The memo is a MemoStore, so the same search can run with a dense
+// table, a HashMap or a bounded LRU cache and report how each one does.
+
+use crate::dp::table_weights;
+use crate::item::{check_instance, make_empty_selection, Item, Solution};
+use crate::number::{Number, NumberError};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::mem::size_of;
+use std::str::FromStr;
+
+// The memo's key: the next item's index and the remaining capacity.
+type Key = (usize, usize);
+
+pub trait MemoStore<T> {
+    fn get(&mut self, index: usize, capacity: usize) -> Option<T>;
+    fn insert(&mut self, index: usize, capacity: usize, value: T);
+
+    // The number of values the store holds.
+    fn entries(&self) -> usize;
+
+    // The approximate number of bytes the store uses.
+    fn memory(&self) -> usize;
+
+    // The number of values the store dropped to make room.
+    fn evictions(&self) -> u64 {
+        0
+    }
+}
+
+// A table with a cell for every subproblem, allocated up front.
+pub struct DenseMemo<T> {
+    num_columns: usize,
+    cells: Vec<Option<T>>,
+    entries: usize,
+}
+
+impl<T: Copy> DenseMemo<T> {
+    pub fn new(num_items: usize, capacity: usize) -> Self {
+        Self {
+            num_columns: capacity + 1,
+            cells: vec![None; num_items * (capacity + 1)],
+            entries: 0,
+        }
+    }
+}
+
+impl<T: Copy> MemoStore<T> for DenseMemo<T> {
+    fn get(&mut self, index: usize, capacity: usize) -> Option<T> {
+        self.cells[index * self.num_columns + capacity]
+    }
+
+    fn insert(&mut self, index: usize, capacity: usize, value: T) {
+        let cell = &mut self.cells[index * self.num_columns + capacity];
+        if cell.is_none() {
+            self.entries += 1;
+        }
+        *cell = Some(value);
+    }
+
+    fn entries(&self) -> usize {
+        self.entries
+    }
+
+    fn memory(&self) -> usize {
+        self.cells.capacity() * size_of::<Option<T>>()
+    }
+}
+
+// A HashMap that only holds the subproblems the search reaches.
+#[derive(Default)]
+pub struct HashMemo<T> {
+    map: HashMap<Key, T>,
+}
+
+impl<T> HashMemo<T> {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Copy> MemoStore<T> for HashMemo<T> {
+    fn get(&mut self, index: usize, capacity: usize) -> Option<T> {
+        self.map.get(&(index, capacity)).copied()
+    }
+
+    fn insert(&mut self, index: usize, capacity: usize, value: T) {
+        self.map.insert((index, capacity), value);
+    }
+
+    fn entries(&self) -> usize {
+        self.map.len()
+    }
+
+    // A hashbrown slot holds the pair plus one control byte.
+    fn memory(&self) -> usize {
+        self.map.capacity() * (size_of::<(Key, T)>() + 1)
+    }
+}
+
+// A HashMap that holds at most max_entries values. When it is full,
+// the value that was used least recently makes room for the new one.
+pub struct LruMemo<T> {
+    max_entries: usize,
+    // The value and the time it was last used.
+    map: HashMap<Key, (T, u64)>,
+    // The keys by the time they were last used.
+    by_use: BTreeMap<u64, Key>,
+    clock: u64,
+    evictions: u64,
+}
+
+impl<T> LruMemo<T> {
+    pub fn new(max_entries: usize) -> Self {
+        assert!(max_entries > 0, "An LRU memo needs room for an entry");
+        Self {
+            max_entries,
+            map: HashMap::new(),
+            by_use: BTreeMap::new(),
+            clock: 0,
+            evictions: 0,
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+impl<T: Copy> MemoStore<T> for LruMemo<T> {
+    fn get(&mut self, index: usize, capacity: usize) -> Option<T> {
+        let now = self.tick();
+        let (value, last_used) = self.map.get_mut(&(index, capacity))?;
+        self.by_use.remove(last_used);
+        self.by_use.insert(now, (index, capacity));
+        *last_used = now;
+        Some(*value)
+    }
+
+    fn insert(&mut self, index: usize, capacity: usize, value: T) {
+        let now = self.tick();
+        let key = (index, capacity);
+        if let Some((_, last_used)) = self.map.insert(key, (value, now)) {
+            self.by_use.remove(&last_used);
+        } else if self.map.len() > self.max_entries {
+            let (_, oldest) = self.by_use.pop_first().expect("The memo isn't empty");
+            self.map.remove(&oldest);
+            self.evictions += 1;
+        }
+        self.by_use.insert(now, key);
+    }
+
+    fn entries(&self) -> usize {
+        self.map.len()
+    }
+
+    // A BTreeMap entry costs about twice its size, with the nodes' slack.
+    fn memory(&self) -> usize {
+        self.map.capacity() * (size_of::<(Key, (T, u64))>() + 1)
+            + self.by_use.len() * 2 * size_of::<(u64, Key)>()
+    }
+
+    fn evictions(&self) -> u64 {
+        self.evictions
+    }
+}
+
+// The kinds of memo stores, for choosing one by name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoKind {
+    Dense,
+    Hash,
+    // An LRU cache with room for this many values.
+    Lru(usize),
+}
+
+impl MemoKind {
+    pub fn make_store<T: Copy + 'static>(
+        &self,
+        num_items: usize,
+        capacity: usize,
+    ) -> Box<dyn MemoStore<T>> {
+        match *self {
+            MemoKind::Dense => Box::new(DenseMemo::new(num_items, capacity)),
+            MemoKind::Hash => Box::new(HashMemo::new()),
+            MemoKind::Lru(max_entries) => Box::new(LruMemo::new(max_entries)),
+        }
+    }
+}
+
+impl fmt::Display for MemoKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MemoKind::Dense => write!(f, "dense"),
+            MemoKind::Hash => write!(f, "hash"),
+            MemoKind::Lru(max_entries) => write!(f, "lru:{}", max_entries),
+        }
+    }
+}
+
+// Parse "dense", "hash" or "lru:N".
+impl FromStr for MemoKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "dense" => Ok(MemoKind::Dense),
+            None if s == "hash" => Ok(MemoKind::Hash),
+            Some(("lru", max_entries)) => match max_entries.parse() {
+                Ok(max_entries) if max_entries > 0 => Ok(MemoKind::Lru(max_entries)),
+                _ => Err(format!("'{}' is not a positive LRU size", max_entries)),
+            },
+            _ => Err(format!("unknown memo store '{}'", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    // Calls of the recursive function, including the reconstruction.
+    pub calls: u64,
+    // Subproblems the memo knew the answer to.
+    pub hits: u64,
+    // Subproblems the search had to solve.
+    pub misses: u64,
+    // Values the store held at the end and dropped on the way.
+    pub entries: usize,
+    pub evictions: u64,
+    // Approximate bytes the store used at the end.
+    pub memory: usize,
+}
+
+impl MemoStats {
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+// Solve the 0/1 knapsack problem top-down with a HashMap memo.
+// Return the best assignment, its value and the number of calls.
+pub fn memoized_search<T: Number + 'static>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+) -> Solution<T> {
+    let (solution, _) = memoized_search_with(items, allowed_weight, MemoKind::Hash);
+    solution
+}
+
+// Solve with the given kind of memo and also return the memo's statistics.
+pub fn memoized_search_with<T: Number + 'static>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    kind: MemoKind,
+) -> (Solution<T>, MemoStats) {
+    try_memoized_search_with(items, allowed_weight, kind)
+        .unwrap_or_else(|error| panic!("memoized search: {}", error))
+}
+
+// Memoized search that returns an error if the items' totals don't
+// fit into T or the weights and allowed weight aren't whole numbers.
+pub fn try_memoized_search_with<T: Number + 'static>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    kind: MemoKind,
+) -> Result<(Solution<T>, MemoStats), NumberError> {
+    check_instance(items, allowed_weight)?;
+    let capacity = allowed_weight.to_index().ok_or(NumberError::NotAnIndex)?;
+    let weights = table_weights(items)?;
+    let mut store = kind.make_store(items.len(), capacity);
+    let mut stats = MemoStats::default();
+
+    let value = do_memoized_search(items, &weights, 0, capacity, store.as_mut(), &mut stats);
+
+    // Item i is selected if taking it is what makes the best value of
+    // items i.. better than the best value of items i + 1...
+    let mut solution = make_empty_selection(items);
+    let mut c = capacity;
+    for i in 0..items.len() {
+        let rest = do_memoized_search(items, &weights, i + 1, c, store.as_mut(), &mut stats);
+        let here = do_memoized_search(items, &weights, i, c, store.as_mut(), &mut stats);
+        if here != rest {
+            solution[i].is_selected = true;
+            c -= weights[i];
+        }
+    }
+
+    stats.entries = store.entries();
+    stats.evictions = store.evictions();
+    stats.memory = store.memory();
+    let calls = i32::try_from(stats.calls).unwrap_or(i32::MAX);
+    Ok(((solution, value, calls), stats))
+}
+
+// Return the best value of the items from next_index on with the
+// given capacity left.
+fn do_memoized_search<T: Number>(
+    items: &[Item<T>],
+    weights: &[usize],
+    next_index: usize,
+    capacity: usize,
+    store: &mut dyn MemoStore<T>,
+    stats: &mut MemoStats,
+) -> T {
+    stats.calls += 1;
+    if next_index >= items.len() {
+        return T::zero();
+    }
+    if let Some(value) = store.get(next_index, capacity) {
+        stats.hits += 1;
+        return value;
+    }
+    stats.misses += 1;
+
+    // Skip next item:
+    let mut best = do_memoized_search(items, weights, next_index + 1, capacity, store, stats);
+
+    // Select next item:
+    let weight = weights[next_index];
+    if weight <= capacity {
+        let value = items[next_index].value
+            + do_memoized_search(
+                items,
+                weights,
+                next_index + 1,
+                capacity - weight,
+                store,
+                stats,
+            );
+        if value > best {
+            best = value;
+        }
+    }
+
+    store.insert(next_index, capacity, best);
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dp::dynamic_programming;
+    use crate::exhaustive::exhaustive_search;
+    use crate::item::{copy_items, make_items, sum_weights};
+    use crate::verify::verify;
+    use utils::Prng;
+
+    const KINDS: [MemoKind; 4] = [
+        MemoKind::Dense,
+        MemoKind::Hash,
+        MemoKind::Lru(1000),
+        MemoKind::Lru(16),
+    ];
+
+    #[test]
+    fn every_store_finds_the_optimum() {
+        for seed in 0..30 {
+            let mut prng = Prng::new_with_seed(seed);
+            let items = make_items(&mut prng, 12, 1, 10, 4, 10);
+            let allowed_weight = sum_weights(&items, true) / 2;
+            let (_, expected, _) = dynamic_programming(&mut copy_items(&items), allowed_weight);
+
+            for kind in KINDS {
+                let ((solution, value, _), _) =
+                    memoized_search_with(&mut copy_items(&items), allowed_weight, kind);
+                assert_eq!(value, expected, "seed {seed}, {kind}");
+                verify(&items, allowed_weight, &solution, value).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn memo_makes_the_search_pseudo_polynomial() {
+        let mut prng = Prng::new_with_seed(1337);
+        let items = make_items(&mut prng, 16, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        let (_, _, exhaustive_calls) = exhaustive_search(&mut copy_items(&items), allowed_weight);
+
+        let subproblems = items.len() as u64 * (allowed_weight as u64 + 1);
+        let ((_, _, calls), stats) =
+            memoized_search_with(&mut copy_items(&items), allowed_weight, MemoKind::Dense);
+        assert!(stats.misses <= subproblems);
+        assert_eq!(stats.entries as u64, stats.misses);
+        assert!(stats.hits > 0);
+        assert!((calls as u64) < subproblems * 4);
+        assert!(calls * 10 < exhaustive_calls);
+        assert_eq!(
+            stats.memory,
+            subproblems as usize * size_of::<Option<i32>>()
+        );
+
+        // The HashMap only stores the subproblems the search reaches.
+        let (_, hash_stats) =
+            memoized_search_with(&mut copy_items(&items), allowed_weight, MemoKind::Hash);
+        assert_eq!(hash_stats.entries, stats.entries);
+        assert_eq!(hash_stats.hits, stats.hits);
+    }
+
+    #[test]
+    fn lru_memo_stays_bounded() {
+        let mut prng = Prng::new_with_seed(1337);
+        let items = make_items(&mut prng, 16, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        let (_, unbounded) =
+            memoized_search_with(&mut copy_items(&items), allowed_weight, MemoKind::Hash);
+        let (_, bounded) =
+            memoized_search_with(&mut copy_items(&items), allowed_weight, MemoKind::Lru(50));
+        assert_eq!(bounded.entries, 50);
+        assert!(bounded.evictions > 0);
+        assert!(bounded.misses > unbounded.misses);
+        assert_eq!(unbounded.evictions, 0);
+    }
+
+    #[test]
+    fn lru_memo_drops_the_least_recently_used() {
+        let mut memo = LruMemo::new(2);
+        memo.insert(0, 0, 10);
+        memo.insert(0, 1, 11);
+        assert_eq!(memo.get(0, 0), Some(10));
+        memo.insert(0, 2, 12);
+        assert_eq!(memo.get(0, 1), None);
+        assert_eq!(memo.get(0, 0), Some(10));
+        assert_eq!(memo.get(0, 2), Some(12));
+        assert_eq!((memo.entries(), memo.evictions()), (2, 1));
+    }
+
+    #[test]
+    fn kinds_parse_by_name() {
+        assert_eq!("dense".parse(), Ok(MemoKind::Dense));
+        assert_eq!("lru:64".parse(), Ok(MemoKind::Lru(64)));
+        assert!("lru:0".parse::<MemoKind>().is_err());
+        assert!("table".parse::<MemoKind>().is_err());
+        assert_eq!(MemoKind::Lru(64).to_string(), "lru:64");
+    }
+}
@@ -0,0 +1,326 @@
+// The numeric types item values and weights can have.
+//
+// The solvers assume values and weights are non-negative. Then every
+// sum a solver forms is a sum over a subset of the items, so if the
+// totals of all values and all weights fit into the type, nothing
+// the solver adds up can overflow. check_instance checks exactly that
+// before a solver starts, which turns overflow into an error instead
+// of a wrong answer.
+//
+// The exhaustive search, branch and bound, Rod's technique, the dynamic
+// program and greedy are generic, and so are the orders, the harness and
+// the verifier. Preprocessing, the subset sum solvers, the memoized
+// search, the metaheuristics and the solver registry keep i32 items.
+
+use num::{Num, ToPrimitive};
+use std::cmp::Ordering;
+use std::fmt;
+
+pub trait Number: Num + ToPrimitive + Copy + PartialOrd + fmt::Display + fmt::Debug {
+    // Return self + other, or None if the sum doesn't fit.
+    // For f64 the sum doesn't fit if it isn't finite.
+    fn checked_sum(self, other: Self) -> Option<Self>;
+
+    // Return self as a table index, or None if it isn't a whole
+    // non-negative number that fits into a usize.
+    fn to_index(self) -> Option<usize>;
+
+    // Compare a_value / a_weight with b_value / b_weight without dividing.
+    fn compare_ratios(a_value: Self, a_weight: Self, b_value: Self, b_weight: Self) -> Ordering;
+
+    // Return self * mul / div, rounded down for integers. The product
+    // may exceed Self as long as the result fits.
+    fn mul_div(self, mul: Self, div: Self) -> Self;
+
+    fn as_f64(self) -> f64 {
+        self.to_f64().unwrap_or(f64::INFINITY)
+    }
+}
+
+// Integers whose cross products fit into the wider type $wide.
+macro_rules! impl_number {
+    ($($t:ty => $wide:ty),*) => {
+        $(
+            impl Number for $t {
+                fn checked_sum(self, other: Self) -> Option<Self> {
+                    self.checked_add(other)
+                }
+
+                fn to_index(self) -> Option<usize> {
+                    self.to_usize()
+                }
+
+                fn compare_ratios(a_value: Self, a_weight: Self, b_value: Self, b_weight: Self) -> Ordering {
+                    let left = a_value as $wide * b_weight as $wide;
+                    let right = b_value as $wide * a_weight as $wide;
+                    left.cmp(&right)
+                }
+
+                fn mul_div(self, mul: Self, div: Self) -> Self {
+                    (self as $wide * mul as $wide / div as $wide) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(i32 => i64, i64 => i128, u64 => u128);
+
+impl Number for u128 {
+    fn checked_sum(self, other: Self) -> Option<Self> {
+        self.checked_add(other)
+    }
+
+    fn to_index(self) -> Option<usize> {
+        self.to_usize()
+    }
+
+    // There is no wider type, so fall back to floating point
+    // if a cross product doesn't fit.
+    fn compare_ratios(a_value: Self, a_weight: Self, b_value: Self, b_weight: Self) -> Ordering {
+        match (a_value.checked_mul(b_weight), b_value.checked_mul(a_weight)) {
+            (Some(left), Some(right)) => left.cmp(&right),
+            _ => {
+                let left = a_value as f64 / a_weight as f64;
+                let right = b_value as f64 / b_weight as f64;
+                left.total_cmp(&right)
+            }
+        }
+    }
+
+    fn mul_div(self, mul: Self, div: Self) -> Self {
+        match self.checked_mul(mul) {
+            Some(product) => product / div,
+            None => (self as f64 * mul as f64 / div as f64) as u128,
+        }
+    }
+}
+
+impl Number for f64 {
+    fn checked_sum(self, other: Self) -> Option<Self> {
+        let sum = self + other;
+        sum.is_finite().then_some(sum)
+    }
+
+    fn to_index(self) -> Option<usize> {
+        if self.fract() != 0.0 {
+            return None;
+        }
+        self.to_usize()
+    }
+
+    fn compare_ratios(a_value: Self, a_weight: Self, b_value: Self, b_weight: Self) -> Ordering {
+        (a_value * b_weight).total_cmp(&(b_value * a_weight))
+    }
+
+    fn mul_div(self, mul: Self, div: Self) -> Self {
+        self * mul / div
+    }
+}
+
+// Compare numbers that are only PartialOrd. check_instance rejects
+// NaN, so solvers never see numbers that don't compare.
+pub fn compare<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberError {
+    // The total of the values or of the weights doesn't fit into the type.
+    Overflow,
+    // A value or weight is negative (or not a number).
+    Negative,
+    // The dynamic program needs whole weights that fit into a usize.
+    NotAnIndex,
+    // A number has more decimals than scaling to integers allows.
+    TooManyDecimals,
+    // A real number is infinite or not a number.
+    NotFinite,
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumberError::Overflow => write!(f, "the totals don't fit into the number type"),
+            NumberError::Negative => write!(f, "values and weights must be non-negative"),
+            NumberError::NotAnIndex => write!(f, "weights must be whole numbers for the table"),
+            NumberError::TooManyDecimals => write!(f, "a number has too many decimals to scale"),
+            NumberError::NotFinite => write!(f, "values and weights must be finite numbers"),
+        }
+    }
+}
+
+// Add up the numbers. Return an error if the sum doesn't fit.
+pub fn checked_total<T: Number>(numbers: impl IntoIterator<Item = T>) -> Result<T, NumberError> {
+    numbers.into_iter().try_fold(T::zero(), |total, number| {
+        total.checked_sum(number).ok_or(NumberError::Overflow)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::branch_bound::{branch_and_bound, try_branch_and_bound_limited};
+    use crate::dp::{dynamic_programming, try_dynamic_programming};
+    use crate::exhaustive::exhaustive_search;
+    use crate::greedy::greedy;
+    use crate::item::{copy_items, make_items, sum_weights, Item};
+    use crate::limits::Limits;
+    use crate::rods::{rods_technique, rods_technique_sorted, try_rods_technique_limited};
+    use crate::verify::verify;
+    use utils::Prng;
+
+    fn convert<T>(items: &[Item], to: impl Fn(i32) -> T) -> Vec<Item<T>> {
+        items
+            .iter()
+            .map(|item| Item {
+                value: to(item.value),
+                weight: to(item.weight),
+                is_selected: false,
+            })
+            .collect()
+    }
+
+    // Run the generic solvers, verify their solutions
+    // and return their values as f64.
+    fn solve_all<T: Number>(items: &[Item<T>], allowed_weight: T) -> Vec<f64> {
+        [
+            exhaustive_search(&mut copy_items(items), allowed_weight),
+            branch_and_bound(&mut copy_items(items), allowed_weight),
+            rods_technique(&mut copy_items(items), allowed_weight),
+            rods_technique_sorted(&mut copy_items(items), allowed_weight),
+            dynamic_programming(&mut copy_items(items), allowed_weight),
+            greedy(&mut copy_items(items), allowed_weight),
+        ]
+        .into_iter()
+        .map(|(solution, value, _)| {
+            verify(items, allowed_weight, &solution, value).unwrap();
+            value.as_f64()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn solvers_agree_across_number_types() {
+        for seed in 0..20 {
+            let mut prng = Prng::new_with_seed(seed);
+            let items = make_items(&mut prng, 12, 1, 10, 4, 10);
+            let allowed_weight = sum_weights(&items, true) / 2;
+
+            let expected = solve_all(&items, allowed_weight);
+            assert!(expected[1..5].iter().all(|&value| value == expected[0]));
+            assert_eq!(
+                solve_all(&convert(&items, |n| n as i64), allowed_weight as i64),
+                expected
+            );
+            assert_eq!(
+                solve_all(&convert(&items, |n| n as u64), allowed_weight as u64),
+                expected
+            );
+            assert_eq!(
+                solve_all(&convert(&items, |n| n as u128), allowed_weight as u128),
+                expected
+            );
+            assert_eq!(
+                solve_all(&convert(&items, |n| n as f64), allowed_weight as f64),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn large_totals_need_a_wider_type() {
+        let values = [1_500_000_000, 1_400_000_000, 900_000_000];
+        let items: Vec<Item> = values
+            .iter()
+            .map(|&value| Item {
+                value,
+                weight: 1,
+                is_selected: false,
+            })
+            .collect();
+        assert_eq!(
+            try_branch_and_bound_limited(&mut copy_items(&items), 2, &Limits::none()).err(),
+            Some(NumberError::Overflow)
+        );
+
+        let mut wide = convert(&items, |n| n as u64);
+        let (_, value, _) = dynamic_programming(&mut wide, 2);
+        assert_eq!(value, 2_900_000_000);
+        let result = try_branch_and_bound_limited(&mut wide, 2, &Limits::none()).unwrap();
+        assert_eq!(result.value, 2_900_000_000);
+        assert_eq!(
+            try_rods_technique_limited(&mut copy_items(&items), 2, &Limits::none()).err(),
+            Some(NumberError::Overflow)
+        );
+        let result = try_rods_technique_limited(&mut wide, 2, &Limits::none()).unwrap();
+        let verified = verify(&wide, 2, &result.items, result.value).unwrap();
+        assert_eq!(verified.upper_bound, Some(2_900_000_000));
+        assert!(verified.proven_optimal);
+    }
+
+    #[test]
+    fn fractional_weights_need_a_search() {
+        let mut items = vec![
+            Item {
+                value: 3.0,
+                weight: 1.5,
+                is_selected: false,
+            },
+            Item {
+                value: 2.5,
+                weight: 1.25,
+                is_selected: false,
+            },
+        ];
+        assert_eq!(
+            try_dynamic_programming(&mut items, 2.0).err(),
+            Some(NumberError::NotAnIndex)
+        );
+        let (solution, value, _) = branch_and_bound(&mut items, 2.0);
+        assert_eq!(value, 3.0);
+        assert!(solution[0].is_selected);
+
+        let negative = vec![Item {
+            value: -1.0,
+            weight: 1.0,
+            is_selected: false,
+        }];
+        assert_eq!(
+            try_dynamic_programming(&mut copy_items(&negative), 2.0).err(),
+            Some(NumberError::Negative)
+        );
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert_eq!(checked_total([i32::MAX, 1]), Err(NumberError::Overflow));
+        assert_eq!(checked_total([u64::MAX - 1, 1]), Ok(u64::MAX));
+        assert_eq!(
+            checked_total([f64::MAX, f64::MAX]),
+            Err(NumberError::Overflow)
+        );
+        assert_eq!(checked_total::<u128>([]), Ok(0));
+    }
+
+    #[test]
+    fn ratios_compare_exactly() {
+        // big / 12 < big / 9, and the cross products overflow i64.
+        let big = i64::MAX / 2;
+        assert_eq!(i64::compare_ratios(big, 3 * 4, big, 3 * 3), Ordering::Less);
+        assert_eq!(u128::compare_ratios(1, 3, 2, 5), Ordering::Less);
+        assert_eq!(
+            u128::compare_ratios(u128::MAX, 3, u128::MAX, 2),
+            Ordering::Less
+        );
+        assert_eq!(f64::compare_ratios(1.0, 3.0, 1.0, 3.0), Ordering::Equal);
+    }
+
+    #[test]
+    fn indices_must_be_whole() {
+        assert_eq!(7.0.to_index(), Some(7));
+        assert_eq!(7.5.to_index(), None);
+        assert_eq!((-1i64).to_index(), None);
+        assert_eq!(u128::MAX.to_index(), None);
+    }
+}
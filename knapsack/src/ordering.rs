@@ -0,0 +1,171 @@
+// Orders in which the tree searches consider the items.
+//
+// A tree search decides about the items one at a time, so the order
+// changes how early good solutions are found and how much is pruned.
+// with_order wraps any algorithm so it sees the items in a given order.
+
+use crate::dominance::block_counts;
+use crate::greedy::ratio_order;
+use crate::harness::Algorithm;
+use crate::item::{copy_items, make_empty_selection, Item, Solution};
+use crate::number::{compare, Number};
+use std::cmp::{Ordering, Reverse};
+use utils::{Prng, Sampling};
+
+#[derive(Clone, Copy)]
+pub enum ItemOrder<T = i32> {
+    // The order in which the items were generated.
+    Generation,
+    // Highest value/weight ratio first.
+    Ratio,
+    // Most valuable first.
+    Value,
+    // Lightest first.
+    Weight,
+    // Items that can block the most other items first.
+    DominanceCount,
+    // A random order drawn from a Prng with the given seed.
+    Random(u32),
+    // Sort with a caller supplied comparator.
+    Custom(fn(&Item<T>, &Item<T>) -> Ordering),
+}
+
+impl<T> ItemOrder<T> {
+    // Every built-in order, for sweeps over all policies.
+    pub fn all(seed: u32) -> Vec<ItemOrder<T>> {
+        vec![
+            ItemOrder::Generation,
+            ItemOrder::Ratio,
+            ItemOrder::Value,
+            ItemOrder::Weight,
+            ItemOrder::DominanceCount,
+            ItemOrder::Random(seed),
+        ]
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            ItemOrder::Generation => "generation".to_string(),
+            ItemOrder::Ratio => "ratio".to_string(),
+            ItemOrder::Value => "value".to_string(),
+            ItemOrder::Weight => "weight".to_string(),
+            ItemOrder::DominanceCount => "dominance".to_string(),
+            ItemOrder::Random(seed) => format!("random({seed})"),
+            ItemOrder::Custom(_) => "custom".to_string(),
+        }
+    }
+}
+
+impl<T: Number> ItemOrder<T> {
+    // Return the item indices in this order.
+    // Sorts are stable, so ties keep their generation order.
+    pub fn indices(&self, items: &[Item<T>]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        match self {
+            ItemOrder::Generation => {}
+            ItemOrder::Ratio => order = ratio_order(items),
+            ItemOrder::Value => order.sort_by(|&a, &b| compare(&items[b].value, &items[a].value)),
+            ItemOrder::Weight => {
+                order.sort_by(|&a, &b| compare(&items[a].weight, &items[b].weight))
+            }
+            ItemOrder::DominanceCount => {
+                let counts = block_counts(items);
+                order.sort_by_key(|&i| Reverse(counts[i]));
+            }
+            ItemOrder::Random(seed) => Prng::new_with_seed(*seed).shuffle(&mut order),
+            ItemOrder::Custom(compare) => order.sort_by(|&a, &b| compare(&items[a], &items[b])),
+        }
+        order
+    }
+}
+
+// Rearrange the items into the given order.
+// Return the indices the items had before.
+pub fn sort_items<T: Number>(items: &mut [Item<T>], order: &ItemOrder<T>) -> Vec<usize> {
+    let indices = order.indices(items);
+    let copies = copy_items(items);
+    for (slot, &i) in items.iter_mut().zip(indices.iter()) {
+        slot.value = copies[i].value;
+        slot.weight = copies[i].weight;
+        slot.is_selected = copies[i].is_selected;
+    }
+    indices
+}
+
+// Return an algorithm that runs alg on the items in the given order
+// and reports its solution in the original order.
+pub fn with_order<T: Number>(
+    alg: &Algorithm<T>,
+    order: ItemOrder<T>,
+) -> impl Fn(&mut [Item<T>], T) -> Solution<T> + '_ {
+    move |items: &mut [Item<T>], allowed_weight: T| {
+        let mut sorted = copy_items(items);
+        let indices = sort_items(&mut sorted, &order);
+        let (solution, value, calls) = alg(&mut sorted, allowed_weight);
+        (restore_order(items, &solution, &indices), value, calls)
+    }
+}
+
+// Return the selection of a solution of the sorted items
+// as a selection of the original items.
+pub fn restore_order<T: Copy>(
+    original: &[Item<T>],
+    solution: &[Item<T>],
+    indices: &[usize],
+) -> Vec<Item<T>> {
+    let mut result = make_empty_selection(original);
+    for (item, &i) in solution.iter().zip(indices.iter()) {
+        result[i].is_selected = item.is_selected;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exhaustive::exhaustive_search;
+    use crate::item::{make_items, sum_weights};
+    use crate::verify::verify;
+
+    #[test]
+    fn orders_are_permutations() {
+        let mut prng = Prng::new_with_seed(1337);
+        let items = make_items(&mut prng, 30, 1, 10, 4, 10);
+        let mut orders: Vec<ItemOrder> = ItemOrder::all(7);
+        orders.push(ItemOrder::Custom(|a, b| b.weight.cmp(&a.weight)));
+
+        for order in orders {
+            let mut indices = order.indices(&items);
+            indices.sort_unstable();
+            assert_eq!(indices, (0..30).collect::<Vec<usize>>(), "{}", order.name());
+        }
+    }
+
+    #[test]
+    fn custom_order_sorts_with_comparator() {
+        let mut prng = Prng::new_with_seed(1337);
+        let mut items = make_items(&mut prng, 20, 1, 10, 4, 10);
+        sort_items(
+            &mut items,
+            &ItemOrder::Custom(|a, b| b.weight.cmp(&a.weight)),
+        );
+        assert!(items
+            .windows(2)
+            .all(|pair| pair[0].weight >= pair[1].weight));
+    }
+
+    #[test]
+    fn solutions_are_in_original_order() {
+        let mut prng = Prng::new_with_seed(42);
+        let items = make_items(&mut prng, 12, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        let (_, expected, _) = exhaustive_search(&mut copy_items(&items), allowed_weight);
+
+        for order in ItemOrder::all(3) {
+            let (solution, value, _) =
+                with_order(&exhaustive_search, order)(&mut copy_items(&items), allowed_weight);
+            assert_eq!(value, expected);
+            verify(&items, allowed_weight, &solution, value).unwrap();
+        }
+    }
+}
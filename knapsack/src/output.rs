@@ -0,0 +1,373 @@
+// Machine-readable results, for notebooks and dashboards.
+//
+// A Record holds everything about one run: the parameters the
+// instance was made from, the selected item indices, the value,
+// weight, calls and elapsed time. The records are written as a JSON
+// array of objects or as CSV with one row per run. Unlike
+// print_selected, the selection is never cut short.
+
+use crate::harness::{timed, LimitedAlgorithm};
+use crate::item::{copy_items, sum_values, sum_weights, Item, Parameters};
+use crate::limits::Limits;
+use crate::verify::verify;
+use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+const CSV_HEADERS: [&str; 16] = [
+    "solver",
+    "num_items",
+    "min_value",
+    "max_value",
+    "min_weight",
+    "max_weight",
+    "seed",
+    "total_value",
+    "total_weight",
+    "allowed_weight",
+    "selected",
+    "value",
+    "weight",
+    "calls",
+    "elapsed_ms",
+    "proven_optimal",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown output format '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::Json => write!(f, "json"),
+            Format::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+// The options the binaries understand, for their usage message.
+pub const USAGE: &str = "[--interactive | --format json|csv]";
+
+// Return the format the program was asked for with --format json
+// or --format csv, or None for the usual text output.
+// Return an error if the format is missing or unknown.
+pub fn output_format() -> Result<Option<Format>, String> {
+    let args: Vec<String> = env::args().collect();
+    parse_format(&args)
+}
+
+fn parse_format(args: &[String]) -> Result<Option<Format>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--format") else {
+        return Ok(None);
+    };
+    match args.get(i + 1) {
+        Some(arg) => arg.parse().map(Some),
+        None => Err("--format needs json or csv".to_string()),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub solver: String,
+    pub parameters: Parameters,
+    pub total_value: i32,
+    pub total_weight: i32,
+    pub allowed_weight: i32,
+    // The indices of the selected items, in increasing order.
+    pub selected: Vec<usize>,
+    pub value: i32,
+    pub weight: i32,
+    pub calls: i32,
+    pub elapsed: Duration,
+    pub proven_optimal: bool,
+}
+
+impl Record {
+    // Make the record of a solution the solver found on items.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        solver: &str,
+        parameters: Parameters,
+        items: &[Item],
+        allowed_weight: i32,
+        solution: &[Item],
+        value: i32,
+        calls: i32,
+        elapsed: Duration,
+        proven_optimal: bool,
+    ) -> Self {
+        Record {
+            solver: solver.to_string(),
+            parameters,
+            total_value: sum_values(items, true),
+            total_weight: sum_weights(items, true),
+            allowed_weight,
+            selected: selected_indices(solution),
+            value,
+            weight: sum_weights(solution, false),
+            calls,
+            elapsed,
+            proven_optimal,
+        }
+    }
+}
+
+// Return the indices of the selected items.
+pub fn selected_indices(items: &[Item]) -> Vec<usize> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.is_selected)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// Make the items from the parameters, allow half their total
+// weight like the programs do and record a run of the search.
+// Panic if the search returns a solution that doesn't verify.
+pub fn record_limited(
+    solver: &str,
+    alg: &LimitedAlgorithm,
+    parameters: Parameters,
+    limits: &Limits,
+) -> Record {
+    let items = parameters.make_items();
+    let allowed_weight = sum_weights(&items, true) / 2;
+    let mut test_items = copy_items(&items);
+    let (result, elapsed) = timed(|| alg(&mut test_items, allowed_weight, limits));
+    if let Err(violation) = verify(&items, allowed_weight, &result.items, result.value) {
+        panic!("{} returned an invalid solution: {}", solver, violation);
+    }
+    Record::new(
+        solver,
+        parameters,
+        &items,
+        allowed_weight,
+        &result.items,
+        result.value,
+        result.calls,
+        elapsed,
+        result.proven_optimal,
+    )
+}
+
+fn format_millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+// Quote a string for JSON.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn join_indices(indices: &[usize], separator: &str) -> String {
+    let indices: Vec<String> = indices.iter().map(|i| i.to_string()).collect();
+    indices.join(separator)
+}
+
+pub fn write_json(out: &mut impl Write, records: &[Record]) -> io::Result<()> {
+    writeln!(out, "[")?;
+    for (i, record) in records.iter().enumerate() {
+        let parameters = &record.parameters;
+        writeln!(out, "  {{")?;
+        writeln!(out, "    \"solver\": {},", json_string(&record.solver))?;
+        writeln!(out, "    \"instance\": {{")?;
+        writeln!(out, "      \"num_items\": {},", parameters.num_items)?;
+        writeln!(out, "      \"min_value\": {},", parameters.min_value)?;
+        writeln!(out, "      \"max_value\": {},", parameters.max_value)?;
+        writeln!(out, "      \"min_weight\": {},", parameters.min_weight)?;
+        writeln!(out, "      \"max_weight\": {},", parameters.max_weight)?;
+        writeln!(out, "      \"seed\": {},", parameters.seed)?;
+        writeln!(out, "      \"total_value\": {},", record.total_value)?;
+        writeln!(out, "      \"total_weight\": {},", record.total_weight)?;
+        writeln!(out, "      \"allowed_weight\": {}", record.allowed_weight)?;
+        writeln!(out, "    }},")?;
+        writeln!(
+            out,
+            "    \"selected\": [{}],",
+            join_indices(&record.selected, ", ")
+        )?;
+        writeln!(out, "    \"value\": {},", record.value)?;
+        writeln!(out, "    \"weight\": {},", record.weight)?;
+        writeln!(out, "    \"calls\": {},", record.calls)?;
+        writeln!(
+            out,
+            "    \"elapsed_ms\": {},",
+            format_millis(record.elapsed)
+        )?;
+        writeln!(out, "    \"proven_optimal\": {}", record.proven_optimal)?;
+        let separator = if i + 1 < records.len() { "," } else { "" };
+        writeln!(out, "  }}{}", separator)?;
+    }
+    writeln!(out, "]")
+}
+
+// Write a header and a row per record. The selected
+// indices are one field, separated by spaces.
+pub fn write_csv(out: &mut impl Write, records: &[Record]) -> io::Result<()> {
+    writeln!(out, "{}", CSV_HEADERS.join(","))?;
+    for record in records {
+        let parameters = &record.parameters;
+        let fields = [
+            csv_field(&record.solver),
+            parameters.num_items.to_string(),
+            parameters.min_value.to_string(),
+            parameters.max_value.to_string(),
+            parameters.min_weight.to_string(),
+            parameters.max_weight.to_string(),
+            parameters.seed.to_string(),
+            record.total_value.to_string(),
+            record.total_weight.to_string(),
+            record.allowed_weight.to_string(),
+            join_indices(&record.selected, " "),
+            record.value.to_string(),
+            record.weight.to_string(),
+            record.calls.to_string(),
+            format_millis(record.elapsed),
+            record.proven_optimal.to_string(),
+        ];
+        writeln!(out, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
+
+pub fn write_records(out: &mut impl Write, format: Format, records: &[Record]) -> io::Result<()> {
+    match format {
+        Format::Json => write_json(out, records),
+        Format::Csv => write_csv(out, records),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rods::rods_technique_sorted_limited;
+
+    const PARAMETERS: Parameters = Parameters {
+        num_items: 110,
+        min_value: 1,
+        max_value: 10,
+        min_weight: 4,
+        max_weight: 10,
+        seed: 1337,
+    };
+
+    fn make_record() -> Record {
+        record_limited(
+            "rods-technique",
+            &rods_technique_sorted_limited,
+            PARAMETERS,
+            &Limits::none(),
+        )
+    }
+
+    fn to_string(format: Format, records: &[Record]) -> String {
+        let mut out = vec![];
+        write_records(&mut out, format, records).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn the_selection_is_complete() {
+        let record = make_record();
+        // More than print_selected shows.
+        assert!(record.selected.len() > 50);
+        assert!(*record.selected.last().unwrap() > 100);
+
+        let items = PARAMETERS.make_items();
+        let weight: i32 = record.selected.iter().map(|&i| items[i].weight).sum();
+        let value: i32 = record.selected.iter().map(|&i| items[i].value).sum();
+        assert_eq!((value, weight), (record.value, record.weight));
+        assert!(record.weight <= record.allowed_weight);
+        assert!(record.proven_optimal);
+    }
+
+    #[test]
+    fn csv_has_a_header_and_a_row_per_record() {
+        let mut record = make_record();
+        record.selected = vec![0, 3, 7];
+        record.elapsed = Duration::from_micros(1500);
+        let text = to_string(Format::Csv, &[record.clone(), record]);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADERS.join(","));
+        let fields: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(fields.len(), CSV_HEADERS.len());
+        assert_eq!(fields[0], "rods-technique");
+        assert_eq!(fields[10], "0 3 7");
+        assert_eq!(fields[14], "1.500");
+    }
+
+    #[test]
+    fn json_is_an_array_of_objects() {
+        let mut record = make_record();
+        record.solver = "say \"hi\"".to_string();
+        record.selected = vec![2, 5];
+        let text = to_string(Format::Json, &[record.clone(), record]);
+        assert!(text.starts_with("[\n  {\n"));
+        assert!(text.ends_with("  }\n]\n"));
+        assert_eq!(text.matches("\"solver\": \"say \\\"hi\\\"\",").count(), 2);
+        assert_eq!(text.matches("\"selected\": [2, 5],").count(), 2);
+        assert!(text.contains("\"seed\": 1337,"));
+        assert!(text.contains("  },\n  {\n"));
+        assert_eq!(to_string(Format::Json, &[]), "[\n]\n");
+    }
+
+    #[test]
+    fn formats_parse_by_name() {
+        assert_eq!("JSON".parse(), Ok(Format::Json));
+        assert_eq!("csv".parse(), Ok(Format::Csv));
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn bad_format_arguments_are_errors() {
+        let args =
+            |text: &str| -> Vec<String> { text.split_whitespace().map(String::from).collect() };
+        assert_eq!(parse_format(&args("prog")), Ok(None));
+        assert_eq!(
+            parse_format(&args("prog --format csv")),
+            Ok(Some(Format::Csv))
+        );
+        assert!(parse_format(&args("prog --format xml")).is_err());
+        assert!(parse_format(&args("prog --format")).is_err());
+    }
+}
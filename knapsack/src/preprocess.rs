@@ -0,0 +1,251 @@
+// Preprocessing that shrinks an instance before a solver sees it.
+//
+// Each rule removes items without changing the optimal value:
+// 1. Items heavier than the allowed weight can never be selected.
+// 2. An item whose dominators (see dominance::can_block) can't all be
+//    selected together with it can be replaced by one of them.
+// 3. Core reductions compare LP bounds with the greedy value. If every
+//    solution without an item is worth less than the greedy solution,
+//    the item is in every optimal solution and is fixed in. If every
+//    solution with it is worth less, it is fixed out.
+
+use crate::dominance::sweep_block_lists;
+use crate::greedy::{fractional_bound, greedy, ratio_order};
+use crate::harness::{Algorithm, LimitedAlgorithm};
+use crate::item::{copy_items, make_empty_selection, Item, Solution};
+use crate::limits::{Limits, SearchResult};
+
+// How many items each rule removed.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PreprocessReport {
+    pub too_heavy: usize,
+    pub dominated: usize,
+    pub fixed_in: usize,
+    pub fixed_out: usize,
+}
+
+impl PreprocessReport {
+    pub fn total(&self) -> usize {
+        self.too_heavy + self.dominated + self.fixed_in + self.fixed_out
+    }
+}
+
+// A reduced instance.
+pub struct Preprocessed {
+    // The items that are left for the solver.
+    pub items: Vec<Item>,
+    // indices[k] is the index of items[k] in the original items.
+    pub indices: Vec<usize>,
+    // The indices of the original items that are fixed in.
+    pub fixed: Vec<usize>,
+    pub fixed_value: i32,
+    // The weight the solver may still use.
+    pub allowed_weight: i32,
+    pub report: PreprocessReport,
+}
+
+impl Preprocessed {
+    // Turn a solution of the reduced items into a solution of the original items.
+    pub fn expand(&self, original: &[Item], solution: &[Item]) -> Vec<Item> {
+        let mut result = make_empty_selection(original);
+        for &i in self.fixed.iter() {
+            result[i].is_selected = true;
+        }
+        for (item, &i) in solution.iter().zip(self.indices.iter()) {
+            result[i].is_selected = item.is_selected;
+        }
+        result
+    }
+}
+
+// Apply all rules to the items and return the reduced instance.
+pub fn preprocess(items: &[Item], allowed_weight: i32) -> Preprocessed {
+    let mut report = PreprocessReport::default();
+    let mut keep: Vec<usize> = (0..items.len()).collect();
+
+    // Rule 1: drop items that are too heavy.
+    keep.retain(|&i| items[i].weight <= allowed_weight);
+    report.too_heavy = items.len() - keep.len();
+
+    // Rule 2: drop dominated items.
+    let before = keep.len();
+    keep = remove_dominated(items, &keep, allowed_weight);
+    report.dominated = before - keep.len();
+
+    // Rule 3: fix items in or out by comparing bounds with the greedy value.
+    let mut candidates: Vec<Item> = keep.iter().map(|&i| copy_item(&items[i])).collect();
+    let (fixed_in, fixed_out) = core_reductions(&mut candidates, allowed_weight);
+    report.fixed_in = fixed_in.len();
+    report.fixed_out = fixed_out.len();
+
+    let fixed: Vec<usize> = fixed_in.iter().map(|&k| keep[k]).collect();
+    let fixed_value = fixed.iter().map(|&i| items[i].value).sum();
+    let fixed_weight: i32 = fixed.iter().map(|&i| items[i].weight).sum();
+    let remaining_weight = allowed_weight - fixed_weight;
+
+    let mut indices = vec![];
+    for (k, &i) in keep.iter().enumerate() {
+        if fixed_in.contains(&k) || fixed_out.contains(&k) {
+            continue;
+        }
+        // The fixed items may have used up room this item needs.
+        if items[i].weight > remaining_weight {
+            report.too_heavy += 1;
+            continue;
+        }
+        indices.push(i);
+    }
+
+    Preprocessed {
+        items: indices.iter().map(|&i| copy_item(&items[i])).collect(),
+        indices,
+        fixed,
+        fixed_value,
+        allowed_weight: remaining_weight,
+        report,
+    }
+}
+
+fn copy_item(item: &Item) -> Item {
+    Item {
+        value: item.value,
+        weight: item.weight,
+        is_selected: false,
+    }
+}
+
+// Return the kept items that aren't dominated.
+//
+// Item j is dominated by every other item that can block it. Identical
+// items block each other, so only the earlier one counts as dominator.
+// If j's dominators don't fit into the knapsack together with j, any
+// solution holding j misses one of them and can swap j for it.
+fn remove_dominated(items: &[Item], keep: &[usize], allowed_weight: i32) -> Vec<usize> {
+    let kept: Vec<Item> = keep.iter().map(|&i| copy_item(&items[i])).collect();
+    let block_lists = sweep_block_lists(&kept);
+
+    let mut dominator_weights = vec![0i64; kept.len()];
+    for (i, block_list) in block_lists.iter().enumerate() {
+        for &j in block_list {
+            let identical = kept[i].value == kept[j].value && kept[i].weight == kept[j].weight;
+            if !identical || i < j {
+                dominator_weights[j] += kept[i].weight as i64;
+            }
+        }
+    }
+
+    keep.iter()
+        .enumerate()
+        .filter(|&(k, _)| dominator_weights[k] + (kept[k].weight as i64) <= allowed_weight as i64)
+        .map(|(_, &i)| i)
+        .collect()
+}
+
+// Return the indices of the items that are fixed in and fixed out.
+fn core_reductions(items: &mut [Item], allowed_weight: i32) -> (Vec<usize>, Vec<usize>) {
+    let (_, lower_bound, _) = greedy(items, allowed_weight);
+    let order = ratio_order(items);
+
+    let mut fixed_in = vec![];
+    let mut fixed_out = vec![];
+    for j in 0..items.len() {
+        // Without item j.
+        let without = fractional_bound(items, &order, allowed_weight, Some(j)).floor() as i32;
+        if without < lower_bound {
+            fixed_in.push(j);
+            continue;
+        }
+
+        // With item j.
+        let with = items[j].value
+            + fractional_bound(items, &order, allowed_weight - items[j].weight, Some(j)).floor()
+                as i32;
+        if with < lower_bound {
+            fixed_out.push(j);
+        }
+    }
+    (fixed_in, fixed_out)
+}
+
+// Return an algorithm that preprocesses the items, runs alg on the
+// reduced items and expands its solution back to the original items.
+pub fn with_preprocessing(alg: &Algorithm) -> impl Fn(&mut [Item], i32) -> Solution + '_ {
+    move |items: &mut [Item], allowed_weight: i32| {
+        let reduced = preprocess(items, allowed_weight);
+        let (solution, value, calls) = alg(&mut copy_items(&reduced.items), reduced.allowed_weight);
+        (
+            reduced.expand(items, &solution),
+            value + reduced.fixed_value,
+            calls,
+        )
+    }
+}
+
+// with_preprocessing for searches that stop at a limit.
+pub fn with_preprocessing_limited(
+    alg: &LimitedAlgorithm,
+) -> impl Fn(&mut [Item], i32, &Limits) -> SearchResult + '_ {
+    move |items: &mut [Item], allowed_weight: i32, limits: &Limits| {
+        let reduced = preprocess(items, allowed_weight);
+        let mut result = alg(
+            &mut copy_items(&reduced.items),
+            reduced.allowed_weight,
+            limits,
+        );
+        result.items = reduced.expand(items, &result.items);
+        result.value += reduced.fixed_value;
+        result.upper_bound += reduced.fixed_value;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dp::dynamic_programming;
+    use crate::exhaustive::exhaustive_search;
+    use crate::item::{make_item, make_items, sum_weights};
+    use crate::verify::verify;
+    use utils::{Generator, Prng};
+
+    #[test]
+    fn drops_heavy_and_dominated_items() {
+        // Item 2 is too heavy. Item 1 is dominated by item 0 and
+        // both don't fit together, so item 0 can always replace it.
+        let items = vec![make_item(5, 6), make_item(4, 7), make_item(9, 20)];
+        let reduced = preprocess(&items, 10);
+        assert_eq!(reduced.report.too_heavy, 1);
+        assert_eq!(reduced.report.dominated, 1);
+    }
+
+    #[test]
+    fn keeps_one_of_identical_items() {
+        let items = vec![make_item(5, 6), make_item(5, 6)];
+        let reduced = preprocess(&items, 10);
+        assert_eq!(reduced.report.dominated, 1);
+        let (solution, value, _) =
+            with_preprocessing(&exhaustive_search)(&mut copy_items(&items), 10);
+        assert_eq!(value, 5);
+        verify(&items, 10, &solution, 5).unwrap();
+    }
+
+    #[test]
+    fn preserves_the_optimum() {
+        for seed in 0..200 {
+            let mut prng = Prng::new_with_seed(seed);
+            let num_items = prng.next_i32(1, 15);
+            let items = make_items(&mut prng, num_items, 1, 10, 1, 10);
+            let allowed_weight = sum_weights(&items, true) / 2;
+
+            let (_, expected, _) = exhaustive_search(&mut copy_items(&items), allowed_weight);
+
+            let algorithms: [&Algorithm; 2] = [&exhaustive_search, &dynamic_programming];
+            for alg in algorithms {
+                let (solution, value, _) =
+                    with_preprocessing(alg)(&mut copy_items(&items), allowed_weight);
+                assert_eq!(value, expected, "seed {seed}");
+                verify(&items, allowed_weight, &solution, value).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+// Progress reports from the tree searches.
+//
+// An Observer set in the Limits hears about every new incumbent and gets
+// a Progress report every progress interval while the search runs, and
+// once more when it ends. The observer is shared with the search, so its
+// methods take &self.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+pub struct Progress<'a> {
+    pub nodes: i32,
+    pub elapsed: Duration,
+    // The best value found so far, if any. Values of every
+    // number type are reported as f64.
+    pub incumbent: Option<f64>,
+    // The upper bound of the root node.
+    pub bound: Option<f64>,
+    // depth_counts[d] is the number of nodes visited at depth d.
+    pub depth_counts: &'a [i32],
+}
+
+impl Progress<'_> {
+    // Return the deepest level the search has reached.
+    pub fn max_depth(&self) -> usize {
+        self.depth_counts
+            .iter()
+            .rposition(|&count| count > 0)
+            .unwrap_or(0)
+    }
+
+    pub fn mean_depth(&self) -> f64 {
+        let total: i64 = self.depth_counts.iter().map(|&count| count as i64).sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let weighted: i64 = self
+            .depth_counts
+            .iter()
+            .enumerate()
+            .map(|(depth, &count)| depth as i64 * count as i64)
+            .sum();
+        weighted as f64 / total as f64
+    }
+}
+
+pub trait Observer {
+    // Called every progress interval.
+    fn on_progress(&self, _progress: &Progress) {}
+
+    // Called when the search finds a better solution.
+    fn on_incumbent(&self, _value: f64, _nodes: i32) {}
+
+    // Called once when the search ends.
+    fn on_finish(&self, _progress: &Progress) {}
+}
+
+// Keep one status line up to date on stderr.
+pub struct ProgressLine;
+
+impl ProgressLine {
+    fn print(progress: &Progress, end: &str) {
+        let value = |v: Option<f64>| v.map_or("-".to_string(), |v| v.to_string());
+        eprint!(
+            "\rNodes: {:>12}  Incumbent: {:>6}  Bound: {:>6}  Depth: {:>4} (mean {:>6.1})  Elapsed: {:>8.1?}{}",
+            progress.nodes,
+            value(progress.incumbent),
+            value(progress.bound),
+            progress.max_depth(),
+            progress.mean_depth(),
+            progress.elapsed,
+            end
+        );
+        io::stderr().flush().unwrap();
+    }
+}
+
+impl Observer for ProgressLine {
+    fn on_progress(&self, progress: &Progress) {
+        Self::print(progress, "");
+    }
+
+    fn on_finish(&self, progress: &Progress) {
+        Self::print(progress, "\n");
+    }
+}
+
+// Return a cancel flag that is set when the user presses Enter.
+// The thread reading stdin is left behind when the search ends.
+pub fn cancel_on_enter() -> Arc<AtomicBool> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&cancel);
+    thread::spawn(move || {
+        let mut line = String::new();
+        if let Ok(n) = io::stdin().read_line(&mut line) {
+            if n > 0 {
+                flag.store(true, Ordering::Relaxed);
+            }
+        }
+    });
+    cancel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depth_statistics() {
+        let progress = Progress {
+            nodes: 7,
+            elapsed: Duration::ZERO,
+            incumbent: None,
+            bound: None,
+            depth_counts: &[1, 2, 4, 0],
+        };
+        assert_eq!(progress.max_depth(), 2);
+        assert!((progress.mean_depth() - 10.0 / 7.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,242 @@
+// Items with real-valued weights and values, such as weights
+// measured in kilograms with decimals.
+//
+// Branch and bound, greedy and the LP bound work on f64 items as they
+// are, but sums of f64 weights pick up rounding errors: 0.1 + 0.2 is a
+// bit more than 0.3. with_tolerance lets a selection exceed the allowed
+// weight by a small epsilon so these errors don't make it infeasible.
+//
+// The dynamic program needs whole weights. For inputs with a fixed
+// number of decimals, scale_items multiplies everything by 10^decimals
+// and rounds to integers, which is exact as long as no number has more
+// decimals than that.
+
+use crate::item::{make_empty_selection, sum_weights, Item, Solution};
+use crate::number::NumberError;
+
+// The default slack for capacity checks.
+pub const DEFAULT_EPSILON: f64 = 1e-9;
+
+// A scaled number may be this many units in the last place away
+// from a whole number and still count as exact. Decimals like 0.95
+// aren't exact in f64, so scaling them is off by an ulp or two.
+const SCALE_ULPS: f64 = 4.0;
+
+// An algorithm for f64 items.
+pub type RealAlgorithm = dyn Fn(&mut [Item<f64>], f64) -> Solution<f64>;
+
+// An algorithm for scaled items that may fail, like try_dynamic_programming.
+pub type ScaledAlgorithm = dyn Fn(&mut [Item<i64>], i64) -> Result<Solution<i64>, NumberError>;
+
+// Return true if the selected items weigh at most
+// allowed_weight plus epsilon.
+pub fn is_feasible(items: &[Item<f64>], allowed_weight: f64, epsilon: f64) -> bool {
+    sum_weights(items, false) <= allowed_weight + epsilon
+}
+
+// Return an algorithm that treats a selection as feasible if it exceeds
+// the allowed weight by at most epsilon. Every capacity check compares
+// a weight with the allowed weight, so it's enough to raise that.
+pub fn with_tolerance(
+    alg: &RealAlgorithm,
+    epsilon: f64,
+) -> impl Fn(&mut [Item<f64>], f64) -> Solution<f64> + '_ {
+    move |items: &mut [Item<f64>], allowed_weight: f64| alg(items, allowed_weight + epsilon)
+}
+
+// Return the whole number that scaled is within a few ulps of, if any.
+fn whole_number(scaled: f64) -> Option<f64> {
+    let rounded = scaled.round();
+    let ulp = f64::EPSILON * scaled.abs().max(1.0);
+    if (scaled - rounded).abs() <= SCALE_ULPS * ulp {
+        Some(rounded)
+    } else {
+        None
+    }
+}
+
+// Return number * scale rounded to an integer, or an error if the
+// result isn't a whole number or doesn't fit.
+// NaN fails every comparison, so it has to be rejected first.
+fn scale_number(number: f64, scale: f64) -> Result<i64, NumberError> {
+    if !number.is_finite() {
+        return Err(NumberError::NotFinite);
+    }
+    let scaled = number * scale;
+    if scaled < 0.0 {
+        return Err(NumberError::Negative);
+    }
+    if scaled >= i64::MAX as f64 {
+        return Err(NumberError::Overflow);
+    }
+    match whole_number(scaled) {
+        Some(rounded) => Ok(rounded as i64),
+        None => Err(NumberError::TooManyDecimals),
+    }
+}
+
+// Multiply the values, weights and allowed weight by 10^decimals and
+// round them to integers. The allowed weight may have more decimals;
+// unless it is a whole number after scaling it is rounded down. The
+// scaled weights are whole, so that keeps exactly the same selections
+// feasible.
+pub fn scale_items(
+    items: &[Item<f64>],
+    allowed_weight: f64,
+    decimals: u32,
+) -> Result<(Vec<Item<i64>>, i64), NumberError> {
+    let scale = 10f64.powi(decimals as i32);
+    let mut scaled = Vec::with_capacity(items.len());
+    for item in items {
+        scaled.push(Item {
+            value: scale_number(item.value, scale)?,
+            weight: scale_number(item.weight, scale)?,
+            is_selected: item.is_selected,
+        });
+    }
+    if !allowed_weight.is_finite() {
+        return Err(NumberError::NotFinite);
+    }
+    let capacity = allowed_weight * scale;
+    let capacity = whole_number(capacity).unwrap_or(capacity.floor());
+    if capacity < 0.0 {
+        return Err(NumberError::Negative);
+    }
+    if capacity >= i64::MAX as f64 {
+        return Err(NumberError::Overflow);
+    }
+    Ok((scaled, capacity as i64))
+}
+
+// Scale the items, solve the scaled instance with alg and
+// carry its selection back to the f64 items.
+pub fn try_solve_scaled(
+    alg: &ScaledAlgorithm,
+    items: &mut [Item<f64>],
+    allowed_weight: f64,
+    decimals: u32,
+) -> Result<Solution<f64>, NumberError> {
+    let (mut scaled, scaled_weight) = scale_items(items, allowed_weight, decimals)?;
+    let (scaled_solution, scaled_value, calls) = alg(&mut scaled, scaled_weight)?;
+
+    let mut solution = make_empty_selection(items);
+    for (item, scaled_item) in solution.iter_mut().zip(&scaled_solution) {
+        item.is_selected = scaled_item.is_selected;
+    }
+    let value = scaled_value as f64 / 10f64.powi(decimals as i32);
+    Ok((solution, value, calls))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::branch_bound::branch_and_bound;
+    use crate::dp::try_dynamic_programming;
+    use crate::greedy::{greedy, lp_bound};
+    use crate::item::{copy_items, make_item};
+    use utils::{Prng, Sampling};
+
+    #[test]
+    fn tolerance_absorbs_rounding_errors() {
+        // 0.1 + 0.2 > 0.3 in f64.
+        let items = vec![make_item(1.0, 0.1), make_item(1.0, 0.2)];
+        let (_, value, _) = branch_and_bound(&mut copy_items(&items), 0.3);
+        assert_eq!(value, 1.0);
+
+        for alg in [&branch_and_bound as &RealAlgorithm, &greedy] {
+            let tolerant = with_tolerance(alg, DEFAULT_EPSILON);
+            let (solution, value, _) = tolerant(&mut copy_items(&items), 0.3);
+            assert_eq!(value, 2.0);
+            assert!(!is_feasible(&solution, 0.3, 0.0));
+            assert!(is_feasible(&solution, 0.3, DEFAULT_EPSILON));
+        }
+    }
+
+    #[test]
+    fn scaled_dp_matches_branch_and_bound() {
+        let mut prng = Prng::new_with_seed(1337);
+        for _ in 0..20 {
+            // Weights in kilograms with two decimals, values with one.
+            let items: Vec<Item<f64>> = (0..12)
+                .map(|_| {
+                    let value = prng.gen_range(10..100) as f64 / 10.0;
+                    let weight = prng.gen_range(100..1000) as f64 / 100.0;
+                    make_item(value, weight)
+                })
+                .collect();
+            let allowed_weight = sum_weights(&items, true) / 2.0;
+
+            let (solution, value, _) = try_solve_scaled(
+                &try_dynamic_programming,
+                &mut copy_items(&items),
+                allowed_weight,
+                2,
+            )
+            .unwrap();
+            let tolerant = with_tolerance(&branch_and_bound, DEFAULT_EPSILON);
+            let (_, expected, _) = tolerant(&mut copy_items(&items), allowed_weight);
+
+            assert!((value - expected).abs() < 1e-9);
+            assert!(is_feasible(&solution, allowed_weight, DEFAULT_EPSILON));
+            assert!(value <= lp_bound(&items, allowed_weight) + 1e-9);
+        }
+    }
+
+    #[test]
+    fn scaling_is_exact_or_an_error() {
+        let items = vec![make_item(1.5, 0.25), make_item(2.0, 0.7)];
+        let (scaled, capacity) = scale_items(&items, 0.95, 2).unwrap();
+        assert_eq!((scaled[0].value, scaled[0].weight), (150, 25));
+        assert_eq!((scaled[1].value, scaled[1].weight), (200, 70));
+        assert_eq!(capacity, 95);
+
+        assert_eq!(
+            scale_items(&items, 0.95, 1).err(),
+            Some(NumberError::TooManyDecimals)
+        );
+        assert_eq!(
+            scale_items(&[make_item(1e30, 1.0)], 1.0, 0).err(),
+            Some(NumberError::Overflow)
+        );
+        // A relative tolerance would round these to whole numbers.
+        assert_eq!(
+            scale_items(&[make_item(1_000_000.5, 1.0)], 10.0, 0).err(),
+            Some(NumberError::TooManyDecimals)
+        );
+        assert_eq!(
+            scale_items(&[make_item(1.0, 123_456_789.01)], 1e9, 1).err(),
+            Some(NumberError::TooManyDecimals)
+        );
+        let (scaled, _) = scale_items(&[make_item(123_456_789.25, 1.0)], 1.0, 2).unwrap();
+        assert_eq!(scaled[0].value, 12_345_678_925);
+
+        for number in [f64::NAN, f64::INFINITY] {
+            assert_eq!(
+                scale_items(&[make_item(number, 1.0)], 1.0, 0).err(),
+                Some(NumberError::NotFinite)
+            );
+            assert_eq!(
+                scale_items(&[make_item(1.0, number)], 1.0, 0).err(),
+                Some(NumberError::NotFinite)
+            );
+            assert_eq!(
+                scale_items(&items, number, 2).err(),
+                Some(NumberError::NotFinite)
+            );
+        }
+    }
+
+    #[test]
+    fn capacity_is_never_rounded_up() {
+        let (_, capacity) = scale_items(&[], 1_000_000.7, 0).unwrap();
+        assert_eq!(capacity, 1_000_000);
+        let (_, capacity) = scale_items(&[], 0.999, 2).unwrap();
+        assert_eq!(capacity, 99);
+
+        let mut items = vec![make_item(5.0, 1_000_001.0)];
+        let (solution, value, _) =
+            try_solve_scaled(&try_dynamic_programming, &mut items, 1_000_000.7, 0).unwrap();
+        assert_eq!(value, 0.0);
+        assert!(is_feasible(&solution, 1_000_000.7, 0.0));
+    }
+}
@@ -0,0 +1,304 @@
+// Rod's technique: branch and bound that also uses dominance. Once the
+// search leaves an item out, the items it dominates stay out as well.
+
+use crate::branch_bound::Best;
+use crate::dominance::sweep_block_lists;
+use crate::item::{check_instance, copy_items, sum_values, Item, Solution};
+use crate::limits::{Budget, Limits, SearchResult};
+use crate::number::{Number, NumberError};
+use crate::ordering::{restore_order, sort_items, ItemOrder};
+use crate::trace::{Decision, Prune};
+
+// The blocking state of the search.
+//
+// When the search leaves item i out, every later item in i's block list
+// is blocked for the rest of that subtree. An item can be blocked by
+// several items at once, so blocked_by keeps all of its current blockers.
+// Blocks are undone in reverse order when the search backtracks.
+struct Blocks {
+    block_lists: Vec<Vec<usize>>,
+    blocked_by: Vec<Vec<usize>>,
+}
+
+impl Blocks {
+    fn new<T: Number>(items: &[Item<T>]) -> Self {
+        Self {
+            block_lists: sweep_block_lists(items),
+            blocked_by: vec![vec![]; items.len()],
+        }
+    }
+
+    fn is_blocked(&self, index: usize) -> bool {
+        !self.blocked_by[index].is_empty()
+    }
+
+    // Return the item that blocked the item at index most recently.
+    fn blocker(&self, index: usize) -> Option<usize> {
+        self.blocked_by[index].last().copied()
+    }
+
+    // Let the item at index block the later items in its block list.
+    fn block(&mut self, index: usize) {
+        for &other in self.block_lists[index].iter() {
+            if other > index {
+                self.blocked_by[other].push(index);
+            }
+        }
+    }
+
+    // Undo block(index).
+    fn unblock(&mut self, index: usize) {
+        for &other in self.block_lists[index].iter() {
+            if other > index {
+                let blocker = self.blocked_by[other].pop();
+                debug_assert_eq!(blocker, Some(index));
+            }
+        }
+    }
+}
+
+// Recursively assign values in or out of the solution.
+// Return the best assignment, value of that assignment,
+// and the number of function calls we made.
+pub fn rods_technique<T: Number>(items: &mut [Item<T>], allowed_weight: T) -> Solution<T> {
+    rods_technique_limited(items, allowed_weight, &Limits::none()).into_solution()
+}
+
+// Rod's technique that stops when a limit is hit and
+// returns the best solution it found until then.
+pub fn rods_technique_limited<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    limits: &Limits,
+) -> SearchResult<Item<T>, T> {
+    try_rods_technique_limited(items, allowed_weight, limits)
+        .unwrap_or_else(|error| panic!("Rod's technique: {}", error))
+}
+
+// Rod's technique that returns an error if the items' totals don't fit into T.
+pub fn try_rods_technique_limited<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    limits: &Limits,
+) -> Result<SearchResult<Item<T>, T>, NumberError> {
+    check_instance(items, allowed_weight)?;
+    for item in items.iter_mut() {
+        item.is_selected = false;
+    }
+    let remaining_value = sum_values(items, true);
+    let mut blocks = Blocks::new(items);
+    let mut best = Best {
+        items: copy_items(items),
+        value: T::zero(),
+    };
+
+    let mut budget = Budget::new(limits);
+
+    do_rods_technique(
+        items,
+        allowed_weight,
+        0,
+        T::zero(),
+        T::zero(),
+        remaining_value,
+        &mut blocks,
+        &mut best,
+        &mut budget,
+    );
+
+    Ok(budget.finish(best.items, best.value))
+}
+
+// Sort the items so those that block the most come first, then
+// use Rod's technique. Blocking items early prunes more of the tree.
+pub fn rods_technique_sorted<T: Number>(items: &mut [Item<T>], allowed_weight: T) -> Solution<T> {
+    rods_technique_sorted_limited(items, allowed_weight, &Limits::none()).into_solution()
+}
+
+// The search runs on the sorted items, so a trace of
+// it numbers the items in the sorted order.
+pub fn rods_technique_sorted_limited<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    limits: &Limits,
+) -> SearchResult<Item<T>, T> {
+    let mut sorted = copy_items(items);
+    let indices = sort_items(&mut sorted, &ItemOrder::DominanceCount);
+    let mut result = rods_technique_limited(&mut sorted, allowed_weight, limits);
+    result.items = restore_order(items, &result.items, &indices);
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+fn do_rods_technique<T: Number>(
+    items: &mut [Item<T>],
+    allowed_weight: T,
+    next_index: usize,
+    current_value: T,
+    current_weight: T,
+    remaining_value: T,
+    blocks: &mut Blocks,
+    best: &mut Best<T>,
+    budget: &mut Budget<T>,
+) {
+    budget.trace_visit(
+        next_index,
+        Decision::of(items, next_index),
+        current_value,
+        current_weight,
+        current_value + remaining_value,
+        current_value > best.value,
+    );
+
+    // The current selection is a solution in its own right.
+    if current_value > best.value {
+        best.items = copy_items(items);
+        best.value = current_value;
+        budget.found(current_value);
+    }
+
+    if !budget.visit(next_index, || current_value + remaining_value) {
+        budget.trace_prune(next_index, Prune::Limit);
+        return;
+    }
+
+    if next_index >= items.len() {
+        return;
+    }
+
+    if current_value + remaining_value <= best.value {
+        budget.trace_prune(next_index, Prune::Bound);
+        return;
+    }
+
+    let value = items[next_index].value;
+    let weight = items[next_index].weight;
+
+    if current_weight + weight <= allowed_weight && !blocks.is_blocked(next_index) {
+        // Select next item:
+        items[next_index].is_selected = true;
+        do_rods_technique(
+            items,
+            allowed_weight,
+            next_index + 1,
+            current_value + value,
+            current_weight + weight,
+            remaining_value - value,
+            blocks,
+            best,
+            budget,
+        );
+        items[next_index].is_selected = false;
+    } else {
+        let reason = match blocks.blocker(next_index) {
+            Some(blocker) if current_weight + weight <= allowed_weight => Prune::BlockedBy(blocker),
+            _ => Prune::Capacity,
+        };
+        budget.trace_pruned_child(
+            next_index + 1,
+            Decision::Take(next_index),
+            current_value + value,
+            current_weight + weight,
+            current_value + remaining_value,
+            reason,
+        );
+    }
+
+    if current_value + remaining_value - value > best.value {
+        // Don't select next item and block the items it dominates:
+        blocks.block(next_index);
+        do_rods_technique(
+            items,
+            allowed_weight,
+            next_index + 1,
+            current_value,
+            current_weight,
+            remaining_value - value,
+            blocks,
+            best,
+            budget,
+        );
+        blocks.unblock(next_index);
+    } else {
+        budget.trace_pruned_child(
+            next_index + 1,
+            Decision::Skip(next_index),
+            current_value,
+            current_weight,
+            current_value + remaining_value - value,
+            Prune::Bound,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exhaustive::exhaustive_search;
+    use crate::harness::Algorithm;
+    use crate::item::{make_item, make_items, sum_weights};
+    use crate::verify::verify;
+    use utils::{Generator, Prng};
+
+    #[test]
+    fn matches_exhaustive_search() {
+        for seed in 0..300 {
+            let mut prng = Prng::new_with_seed(seed);
+            let num_items = prng.next_i32(1, 15);
+            let items = make_items(&mut prng, num_items, 1, 10, 1, 10);
+            let allowed_weight = sum_weights(&items, true) / 2;
+
+            let (_, expected, _) = exhaustive_search(&mut copy_items(&items), allowed_weight);
+
+            let algorithms: [&Algorithm; 2] = [&rods_technique, &rods_technique_sorted];
+            for alg in algorithms {
+                let (solution, value, _) = alg(&mut copy_items(&items), allowed_weight);
+                assert_eq!(value, expected, "seed {seed}");
+                verify(&items, allowed_weight, &solution, value).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn blocks_are_undone_on_backtrack() {
+        let mut prng = Prng::new_with_seed(1337);
+        let mut items = make_items(&mut prng, 12, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        let remaining_value = sum_values(&items, true);
+        let mut blocks = Blocks::new(&items);
+        let mut best = Best {
+            items: copy_items(&items),
+            value: 0,
+        };
+
+        do_rods_technique(
+            &mut items,
+            allowed_weight,
+            0,
+            0,
+            0,
+            remaining_value,
+            &mut blocks,
+            &mut best,
+            &mut Budget::new(&Limits::none()),
+        );
+
+        assert!(blocks.blocked_by.iter().all(|blockers| blockers.is_empty()));
+        assert!(items.iter().all(|item| !item.is_selected));
+    }
+
+    #[test]
+    fn items_record_every_blocker() {
+        let items = vec![make_item(5, 3), make_item(6, 2), make_item(1, 9)];
+        let mut blocks = Blocks::new(&items);
+
+        blocks.block(0);
+        blocks.block(1);
+        assert_eq!(blocks.blocked_by[2], vec![0, 1]);
+
+        blocks.unblock(1);
+        assert!(blocks.is_blocked(2));
+        blocks.unblock(0);
+        assert!(!blocks.is_blocked(2));
+    }
+}
@@ -0,0 +1,125 @@
+// The registry of knapsack solvers, so tools like the benchmark
+// can look them up by name and run all of them.
+
+use crate::annealing::simulated_annealing;
+use crate::branch_bound::{branch_and_bound, branch_and_bound_limited};
+use crate::dp::dynamic_programming;
+use crate::exhaustive::{exhaustive_search, exhaustive_search_limited};
+use crate::genetic::genetic_algorithm;
+use crate::greedy::greedy;
+use crate::item::{Item, Solution};
+use crate::limits::{Limits, SearchResult};
+use crate::memo::memoized_search;
+use crate::rods::{rods_technique_sorted, rods_technique_sorted_limited};
+
+// A search that stops at the limits.
+pub type LimitedSolve = fn(&mut [Item], i32, &Limits) -> SearchResult;
+
+pub struct Solver {
+    pub name: &'static str,
+    pub solve: fn(&mut [Item], i32) -> Solution,
+    // Tree searches can also stop at a limit.
+    pub solve_limited: Option<LimitedSolve>,
+    // Exact solvers always return an optimal solution.
+    pub exact: bool,
+}
+
+pub fn solvers() -> Vec<Solver> {
+    vec![
+        Solver {
+            name: "exhaustive",
+            solve: exhaustive_search,
+            solve_limited: Some(exhaustive_search_limited),
+            exact: true,
+        },
+        Solver {
+            name: "branch-and-bound",
+            solve: branch_and_bound,
+            solve_limited: Some(branch_and_bound_limited),
+            exact: true,
+        },
+        Solver {
+            name: "rods-technique",
+            solve: rods_technique_sorted,
+            solve_limited: Some(rods_technique_sorted_limited),
+            exact: true,
+        },
+        Solver {
+            name: "dynamic-programming",
+            solve: dynamic_programming,
+            solve_limited: None,
+            exact: true,
+        },
+        Solver {
+            name: "memoized",
+            solve: memoized_search,
+            solve_limited: None,
+            exact: true,
+        },
+        Solver {
+            name: "greedy",
+            solve: greedy,
+            solve_limited: None,
+            exact: false,
+        },
+        Solver {
+            name: "simulated-annealing",
+            solve: simulated_annealing,
+            solve_limited: None,
+            exact: false,
+        },
+        Solver {
+            name: "genetic-algorithm",
+            solve: genetic_algorithm,
+            solve_limited: None,
+            exact: false,
+        },
+    ]
+}
+
+// Return the solver with the given name.
+pub fn find_solver(name: &str) -> Option<Solver> {
+    solvers().into_iter().find(|solver| solver.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::{copy_items, make_items, sum_weights};
+    use crate::verify::verify;
+    use utils::Xoshiro256;
+
+    #[test]
+    fn exact_solvers_agree() {
+        let mut prng = Xoshiro256::new_with_seed(1337);
+        for _ in 0..20 {
+            let items = make_items(&mut prng, 12, 1, 20, 1, 20);
+            let allowed_weight = sum_weights(&items, true) / 2;
+            let (_, expected, _) = exhaustive_search(&mut copy_items(&items), allowed_weight);
+
+            for solver in solvers() {
+                let (solution, value, _) = (solver.solve)(&mut copy_items(&items), allowed_weight);
+                verify(&items, allowed_weight, &solution, value).unwrap();
+                if let Some(solve_limited) = solver.solve_limited {
+                    let result =
+                        solve_limited(&mut copy_items(&items), allowed_weight, &Limits::none());
+                    assert_eq!(result.value, value, "{}", solver.name);
+                    assert!(result.proven_optimal);
+                }
+                if solver.exact {
+                    assert_eq!(value, expected, "{}", solver.name);
+                } else {
+                    assert!(value <= expected, "{}", solver.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solvers_are_found_by_name() {
+        for solver in solvers() {
+            assert!(find_solver(solver.name).is_some());
+        }
+        assert!(find_solver("simplex").is_none());
+    }
+}
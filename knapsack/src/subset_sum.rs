@@ -0,0 +1,256 @@
+// Subset sum: a knapsack whose values equal the weights.
+// The solvers look for the largest sum that doesn't exceed
+// the allowed weight, or decide whether an exact target
+// can be reached.
+
+use crate::dp::{fill_table, item_weights, reconstruct, Multiplicity};
+use crate::item::{make_empty_selection, make_items, Item, Solution};
+use utils::Generator;
+
+// Meet in the middle enumerates up to 2^(n/2) sums for each half
+// of the items. With 44 items that's 4 million sums per half.
+pub const MAX_MEET_IN_MIDDLE_ITEMS: usize = 44;
+
+// Make some random items whose values equal their weights.
+pub fn make_subset_sum_items(
+    prng: &mut dyn Generator,
+    num_items: i32,
+    min_weight: i32,
+    max_weight: i32,
+) -> Vec<Item> {
+    let mut items = make_items(prng, num_items, 0, 1, min_weight, max_weight);
+    for item in items.iter_mut() {
+        item.value = item.weight;
+    }
+    items
+}
+
+// Solve subset sum with a table of reachable sums.
+// Return the best assignment, its sum and the number of table cells.
+pub fn subset_sum_dp(items: &mut [Item], allowed_weight: i32) -> Solution {
+    let capacity = allowed_weight.max(0) as usize;
+    let table = fill_table(
+        &item_weights(items),
+        capacity,
+        Multiplicity::ZeroOne,
+        |c| c == 0,
+        |skip, take, _| skip || take.unwrap_or(false),
+    );
+
+    let last_row = table.row(items.len());
+    let best = (0..=capacity).rev().find(|&c| last_row[c]).unwrap_or(0);
+    let solution = reconstruct(&table, items, best);
+    (solution, best as i32, table.num_cells() as i32)
+}
+
+// A set of reachable sums stored as bits.
+#[derive(Clone)]
+struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    fn new(num_bits: usize) -> Self {
+        Self {
+            words: vec![0; num_bits / 64 + 1],
+        }
+    }
+
+    fn get(&self, bit: usize) -> bool {
+        self.words[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    fn set(&mut self, bit: usize) {
+        self.words[bit / 64] |= 1 << (bit % 64);
+    }
+
+    // Return self | (self << shift), dropping bits beyond the last word.
+    fn or_shifted(&self, shift: usize) -> BitSet {
+        let word_shift = shift / 64;
+        let bit_shift = shift % 64;
+        let mut result = self.clone();
+        for i in (word_shift..self.words.len()).rev() {
+            let source = i - word_shift;
+            let mut shifted = self.words[source] << bit_shift;
+            if bit_shift > 0 && source > 0 {
+                shifted |= self.words[source - 1] >> (64 - bit_shift);
+            }
+            result.words[i] |= shifted;
+        }
+        result
+    }
+}
+
+// Return the reachable sums after each prefix of the items.
+// rows[i] holds the sums reachable with the first i items.
+fn reachable_rows(items: &[Item], capacity: usize) -> Vec<BitSet> {
+    let mut rows = Vec::with_capacity(items.len() + 1);
+    let mut reachable = BitSet::new(capacity);
+    reachable.set(0);
+    rows.push(reachable);
+    for item in items {
+        let next = rows[rows.len() - 1].or_shifted(item.weight as usize);
+        rows.push(next);
+    }
+    rows
+}
+
+// Select the items that make up sum, walking back through the rows.
+fn select_sum(items: &[Item], rows: &[BitSet], sum: usize) -> Vec<Item> {
+    let mut solution = make_empty_selection(items);
+    let mut s = sum;
+    for i in (0..items.len()).rev() {
+        if !rows[i].get(s) {
+            solution[i].is_selected = true;
+            s -= items[i].weight as usize;
+        }
+    }
+    solution
+}
+
+// Solve subset sum by shifting a bitset of reachable sums.
+// Return the best assignment, its sum and the number of words shifted.
+pub fn subset_sum_bitset(items: &mut [Item], allowed_weight: i32) -> Solution {
+    let capacity = allowed_weight.max(0) as usize;
+    let rows = reachable_rows(items, capacity);
+
+    let last_row = &rows[items.len()];
+    let best = (0..=capacity).rev().find(|&s| last_row.get(s)).unwrap_or(0);
+    let words = rows[0].words.len() * items.len();
+    (select_sum(items, &rows, best), best as i32, words as i32)
+}
+
+// Decide whether some of the items add up to exactly target.
+// Return the selection if they do.
+pub fn is_reachable(items: &[Item], target: i32) -> Option<Vec<Item>> {
+    if target < 0 {
+        return None;
+    }
+    let rows = reachable_rows(items, target as usize);
+    if rows[items.len()].get(target as usize) {
+        Some(select_sum(items, &rows, target as usize))
+    } else {
+        None
+    }
+}
+
+// Return every (sum, mask) of the items' subsets with sum <= limit.
+fn subset_sums(items: &[Item], limit: i64) -> Vec<(i64, u64)> {
+    let mut sums = vec![(0i64, 0u64)];
+    for (i, item) in items.iter().enumerate() {
+        let count = sums.len();
+        for j in 0..count {
+            let (sum, mask) = sums[j];
+            let new_sum = sum + item.weight as i64;
+            if new_sum <= limit {
+                sums.push((new_sum, mask | (1 << i)));
+            }
+        }
+    }
+    sums
+}
+
+// Solve subset sum by meeting in the middle. Enumerate the sums of both
+// halves of the items, sort the second half and binary search the best
+// partner for every sum of the first half.
+// Return the best assignment, its sum and the number of sums enumerated.
+// Even the empty selection doesn't fit a negative allowed weight.
+pub fn subset_sum_meet_in_middle(items: &mut [Item], allowed_weight: i32) -> Solution {
+    assert!(
+        items.len() <= MAX_MEET_IN_MIDDLE_ITEMS,
+        "Meet in the middle can't enumerate the subset sums of more than {} items",
+        MAX_MEET_IN_MIDDLE_ITEMS
+    );
+    assert!(
+        allowed_weight >= 0,
+        "The allowed weight must be non-negative"
+    );
+
+    let limit = allowed_weight as i64;
+    let half = items.len() / 2;
+    let first = subset_sums(&items[..half], limit);
+    let mut second = subset_sums(&items[half..], limit);
+    second.sort();
+
+    let mut best = (0i64, 0u64, 0u64);
+    for &(sum, mask) in first.iter() {
+        // Find the largest second half sum <= limit - sum.
+        let index = second.partition_point(|&(s, _)| s <= limit - sum);
+        if index > 0 {
+            let (other_sum, other_mask) = second[index - 1];
+            if sum + other_sum > best.0 {
+                best = (sum + other_sum, mask, other_mask);
+            }
+        }
+    }
+
+    let mut solution = make_empty_selection(items);
+    for (i, item) in solution.iter_mut().enumerate() {
+        item.is_selected = if i < half {
+            best.1 & (1 << i) != 0
+        } else {
+            best.2 & (1 << (i - half)) != 0
+        };
+    }
+    let calls = (first.len() + second.len()) as i32;
+    (solution, best.0 as i32, calls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::{copy_items, sum_weights};
+    use crate::verify::verify_subset_sum;
+    use utils::Prng;
+
+    #[test]
+    fn solvers_agree() {
+        for seed in 0..20 {
+            let mut prng = Prng::new_with_seed(seed);
+            let items = make_subset_sum_items(&mut prng, 20, 10, 100);
+            let allowed_weight = sum_weights(&items, true) / 3;
+
+            let (dp_solution, dp_sum, _) = subset_sum_dp(&mut copy_items(&items), allowed_weight);
+            let (bit_solution, bit_sum, _) =
+                subset_sum_bitset(&mut copy_items(&items), allowed_weight);
+            let (mitm_solution, mitm_sum, _) =
+                subset_sum_meet_in_middle(&mut copy_items(&items), allowed_weight);
+
+            assert_eq!(dp_sum, bit_sum, "seed {seed}");
+            assert_eq!(dp_sum, mitm_sum, "seed {seed}");
+            verify_subset_sum(&items, allowed_weight, &dp_solution, dp_sum).unwrap();
+            verify_subset_sum(&items, allowed_weight, &bit_solution, bit_sum).unwrap();
+            verify_subset_sum(&items, allowed_weight, &mitm_solution, mitm_sum).unwrap();
+        }
+    }
+
+    #[test]
+    fn bitset_shifts_across_words() {
+        let items: Vec<Item> = [60, 70, 3]
+            .iter()
+            .map(|&weight| Item {
+                value: weight,
+                weight,
+                is_selected: false,
+            })
+            .collect();
+        let solution = is_reachable(&items, 133).unwrap();
+        assert!(solution.iter().all(|item| item.is_selected));
+        assert!(is_reachable(&items, 131).is_none());
+        assert!(is_reachable(&items, 0).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "non-negative")]
+    fn meet_in_middle_rejects_negative_weights() {
+        let mut items = make_subset_sum_items(&mut Prng::new_with_seed(1), 4, 1, 10);
+        subset_sum_meet_in_middle(&mut items, -1);
+    }
+
+    #[test]
+    #[should_panic(expected = "more than 44 items")]
+    fn meet_in_middle_rejects_too_many_items() {
+        let mut items = make_subset_sum_items(&mut Prng::new_with_seed(1), 45, 1, 10);
+        subset_sum_meet_in_middle(&mut items, 100);
+    }
+}
@@ -0,0 +1,338 @@
+// Tracing the tree a search explores, to teach and debug pruning.
+//
+// A search with a Trace in its Limits records every node it visits
+// and every child it doesn't visit, with the reason it was pruned.
+// The nodes are kept in the order the search reaches them, so a
+// node's parent always comes before it. Once the trace holds
+// max_nodes nodes it only counts the rest.
+//
+// write_dot writes the tree as a Graphviz DOT graph. Render it with
+// `dot -Tsvg search.dot -o search.svg`.
+
+use crate::harness::LimitedAlgorithm;
+use crate::item::{copy_items, Item};
+use crate::limits::Limits;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::{Arc, Mutex};
+
+// The choice that leads from a node's parent to the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decision {
+    Root,
+    // Select the item with this index.
+    Take(usize),
+    // Leave the item with this index out.
+    Skip(usize),
+}
+
+impl Decision {
+    // Return the decision that leads to the node at depth, which the
+    // search reaches with the item before it selected or not.
+    pub fn of<T>(items: &[Item<T>], depth: usize) -> Decision {
+        match depth {
+            0 => Decision::Root,
+            _ if items[depth - 1].is_selected => Decision::Take(depth - 1),
+            _ => Decision::Skip(depth - 1),
+        }
+    }
+}
+
+// Why a subtree wasn't explored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Prune {
+    // Selecting the item would exceed the allowed weight.
+    Capacity,
+    // The subtree can't beat the best solution found so far.
+    Bound,
+    // Rod's technique: leaving out this earlier item blocks the item.
+    BlockedBy(usize),
+    // The search stopped at a limit.
+    Limit,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceNode {
+    pub parent: Option<usize>,
+    pub depth: usize,
+    pub decision: Decision,
+    pub value: f64,
+    pub weight: f64,
+    // The upper bound the search has for the node's subtree.
+    pub bound: f64,
+    // False for children the search pruned without visiting them.
+    pub visited: bool,
+    pub pruned: Option<Prune>,
+    // The node's selection beat every solution before it.
+    pub improved: bool,
+}
+
+pub struct Trace {
+    nodes: Vec<TraceNode>,
+    max_nodes: usize,
+    dropped: usize,
+    // The recorded nodes on the path to the current node, by depth.
+    path: Vec<usize>,
+}
+
+impl Trace {
+    pub fn new(max_nodes: usize) -> Self {
+        Self {
+            nodes: vec![],
+            max_nodes,
+            dropped: 0,
+            path: vec![],
+        }
+    }
+
+    // Make a trace to put into Limits and read after the search.
+    pub fn shared(max_nodes: usize) -> Arc<Mutex<Trace>> {
+        Arc::new(Mutex::new(Self::new(max_nodes)))
+    }
+
+    pub fn nodes(&self) -> &[TraceNode] {
+        &self.nodes
+    }
+
+    // The number of nodes that didn't fit into the trace.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    // Record a node and return its id, or None if it was dropped.
+    fn add(&mut self, node: TraceNode) -> Option<usize> {
+        let parent = match node.depth {
+            0 => None,
+            depth => Some(*self.path.get(depth - 1)?),
+        };
+        if self.nodes.len() >= self.max_nodes {
+            return None;
+        }
+        self.nodes.push(TraceNode { parent, ..node });
+        Some(self.nodes.len() - 1)
+    }
+
+    // Record a node the search visits.
+    pub(crate) fn visit(&mut self, node: TraceNode) {
+        let depth = node.depth;
+        self.path.truncate(depth);
+        match self.add(node) {
+            Some(id) => self.path.push(id),
+            None => self.dropped += 1,
+        }
+    }
+
+    // Record a child the search prunes without visiting it.
+    pub(crate) fn prune_child(&mut self, node: TraceNode) {
+        if self.add(node).is_none() {
+            self.dropped += 1;
+        }
+    }
+
+    // Mark the visited node at depth as pruned.
+    pub(crate) fn prune(&mut self, depth: usize, reason: Prune) {
+        if self.path.len() == depth + 1 {
+            self.nodes[self.path[depth]].pruned = Some(reason);
+        }
+    }
+
+    pub fn write_dot(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "digraph search {{")?;
+        writeln!(
+            out,
+            "  node [shape=box, style=filled, fontname=\"Helvetica\"];"
+        )?;
+        for (id, node) in self.nodes.iter().enumerate() {
+            let mut label = match node.decision {
+                Decision::Root => "root".to_string(),
+                Decision::Take(i) => format!("take {}", i),
+                Decision::Skip(i) => format!("skip {}", i),
+            };
+            label.push_str(&format!(
+                "\\nv={} w={}\\nbound={}",
+                node.value, node.weight, node.bound
+            ));
+            match node.pruned {
+                Some(Prune::Capacity) => label.push_str("\\npruned: capacity"),
+                Some(Prune::Bound) => label.push_str("\\npruned: bound"),
+                Some(Prune::BlockedBy(i)) => label.push_str(&format!("\\nblocked by {}", i)),
+                Some(Prune::Limit) => label.push_str("\\nstopped: limit"),
+                None if node.improved => label.push_str("\\nnew best"),
+                None => {}
+            }
+            writeln!(
+                out,
+                "  n{} [label=\"{}\", fillcolor=\"{}\"];",
+                id,
+                label,
+                color(node)
+            )?;
+            if let Some(parent) = node.parent {
+                let style = if node.visited { "solid" } else { "dashed" };
+                writeln!(out, "  n{} -> n{} [style={}];", parent, id, style)?;
+            }
+        }
+        if self.dropped > 0 {
+            writeln!(
+                out,
+                "  dropped [shape=note, fillcolor=\"white\", label=\"{} more nodes not shown\"];",
+                self.dropped
+            )?;
+        }
+        writeln!(out, "}}")
+    }
+
+    pub fn write_dot_file(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_dot(&mut out)?;
+        out.flush()
+    }
+}
+
+fn color(node: &TraceNode) -> &'static str {
+    match node.pruned {
+        Some(Prune::Capacity) => "lightpink",
+        Some(Prune::Bound) => "khaki",
+        Some(Prune::BlockedBy(_)) => "plum",
+        Some(Prune::Limit) => "lightgray",
+        None if node.improved => "palegreen",
+        None => "lightblue",
+    }
+}
+
+// Return the file the program was asked to write the
+// search tree to with --trace FILE, if any.
+pub fn trace_path() -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    let i = args.iter().position(|arg| arg == "--trace")?;
+    Some(args.get(i + 1).expect("--trace needs a file name").clone())
+}
+
+// Run the search with a trace of at most max_nodes nodes and write it to path.
+pub fn trace_search(
+    alg: &LimitedAlgorithm,
+    items: &[Item],
+    allowed_weight: i32,
+    limits: &Limits,
+    max_nodes: usize,
+    path: &str,
+) {
+    let trace = Trace::shared(max_nodes);
+    let limits = limits.clone().with_trace(trace.clone());
+    alg(&mut copy_items(items), allowed_weight, &limits);
+
+    let trace = trace.lock().unwrap();
+    match trace.write_dot_file(path) {
+        Ok(()) => println!(
+            "Wrote {} nodes of the search tree to {} ({} more not shown)",
+            trace.nodes().len(),
+            path,
+            trace.dropped()
+        ),
+        Err(error) => println!("Error writing {}: {}", path, error),
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::branch_bound::branch_and_bound_limited;
+    use crate::item::{make_items, sum_weights};
+    use crate::limits::SearchResult;
+    use crate::rods::rods_technique_limited;
+    use utils::Prng;
+
+    fn run_traced(
+        alg: &LimitedAlgorithm,
+        seed: u32,
+        max_nodes: usize,
+    ) -> (SearchResult, Arc<Mutex<Trace>>) {
+        let mut prng = Prng::new_with_seed(seed);
+        let items = make_items(&mut prng, 12, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        let trace = Trace::shared(max_nodes);
+        let limits = Limits::none().with_trace(trace.clone());
+        let result = alg(&mut copy_items(&items), allowed_weight, &limits);
+        (result, trace)
+    }
+
+    fn count(trace: &Trace, reason: Prune) -> usize {
+        trace
+            .nodes()
+            .iter()
+            .filter(|node| node.pruned == Some(reason))
+            .count()
+    }
+
+    #[test]
+    fn trace_matches_the_search() {
+        let (result, trace) = run_traced(&branch_and_bound_limited, 1337, usize::MAX);
+        let trace = trace.lock().unwrap();
+        let nodes = trace.nodes();
+        assert_eq!(trace.dropped(), 0);
+
+        let visited = nodes.iter().filter(|node| node.visited).count();
+        assert_eq!(visited as i32, result.calls);
+        assert_eq!(nodes[0].decision, Decision::Root);
+        for (id, node) in nodes.iter().enumerate().skip(1) {
+            let parent = &nodes[node.parent.unwrap()];
+            assert!(node.parent.unwrap() < id);
+            assert_eq!(node.depth, parent.depth + 1);
+            assert!(node.bound <= parent.bound);
+        }
+
+        let improved: Vec<f64> = nodes
+            .iter()
+            .filter(|node| node.improved)
+            .map(|node| node.value)
+            .collect();
+        assert!(improved.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(improved.last(), Some(&(result.value as f64)));
+
+        assert!(count(&trace, Prune::Capacity) > 0);
+        assert!(count(&trace, Prune::Bound) > 0);
+    }
+
+    #[test]
+    fn rods_trace_shows_blocked_items() {
+        let (result, trace) = run_traced(&rods_technique_limited, 1337, usize::MAX);
+        let trace = trace.lock().unwrap();
+        let blocked = trace
+            .nodes()
+            .iter()
+            .filter(|node| matches!(node.pruned, Some(Prune::BlockedBy(_))))
+            .count();
+        assert!(blocked > 0);
+        let visited = trace.nodes().iter().filter(|node| node.visited).count();
+        assert_eq!(visited as i32, result.calls);
+    }
+
+    #[test]
+    fn large_traces_are_capped() {
+        let (_, trace) = run_traced(&branch_and_bound_limited, 1337, 50);
+        let trace = trace.lock().unwrap();
+        assert_eq!(trace.nodes().len(), 50);
+        assert!(trace.dropped() > 0);
+
+        let mut out = vec![];
+        trace.write_dot(&mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+        assert!(dot.starts_with("digraph search {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.matches(" -> ").count(), 49);
+        assert!(dot.contains(&format!("{} more nodes not shown", trace.dropped())));
+    }
+
+    #[test]
+    fn stopped_searches_mark_the_limit() {
+        let mut prng = Prng::new_with_seed(1337);
+        let items = make_items(&mut prng, 12, 1, 10, 4, 10);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        let trace = Trace::shared(usize::MAX);
+        let limits = Limits::none().with_nodes(5).with_trace(trace.clone());
+        branch_and_bound_limited(&mut copy_items(&items), allowed_weight, &limits);
+        assert!(count(&trace.lock().unwrap(), Prune::Limit) > 0);
+    }
+}
@@ -0,0 +1,289 @@
+// An independent check of a solver's answer.
+//
+// The verifier doesn't trust anything the solver says: it recomputes
+// the weight and value of the selection from the instance and checks
+// that the solution describes the same items, fits and is worth what
+// the solver claims.
+//
+// For the 0/1 knapsack it also checks an LP dual certificate. Every
+// lambda >= 0 gives the upper bound
+//
+//     lambda * allowed_weight + sum of max(0, value - lambda * weight)
+//
+// on the value of any solution. The best lambda is the value/weight
+// ratio of the critical item, the first item in ratio order that no
+// longer fits. Lambda is kept as that ratio, so the bound is exact.
+
+use crate::greedy::ratio_order;
+use crate::item::{sum_values, sum_weights, Item};
+use crate::number::Number;
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Violation<T = i32> {
+    // The solution has a different number of items than the instance.
+    WrongLength { expected: usize, found: usize },
+    // The solution's item differs from the instance's item at this index.
+    ItemChanged(usize),
+    Overweight { weight: T, allowed_weight: T },
+    ValueMismatch { claimed: T, actual: T },
+    // The value is above an upper bound that holds for every solution.
+    AboveBound { value: T, bound: T },
+    // A constraint of a particular model isn't met.
+    Model(String),
+}
+
+impl<T: fmt::Display> fmt::Display for Violation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::WrongLength { expected, found } => {
+                write!(f, "expected {} items, found {}", expected, found)
+            }
+            Violation::ItemChanged(i) => write!(f, "item {} differs from the instance", i),
+            Violation::Overweight {
+                weight,
+                allowed_weight,
+            } => write!(
+                f,
+                "weight {} exceeds the allowed {}",
+                weight, allowed_weight
+            ),
+            Violation::ValueMismatch { claimed, actual } => {
+                write!(
+                    f,
+                    "claimed value {} but the items are worth {}",
+                    claimed, actual
+                )
+            }
+            Violation::AboveBound { value, bound } => {
+                write!(f, "value {} is above the upper bound {}", value, bound)
+            }
+            Violation::Model(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+// A lambda for the LP dual, kept as the ratio numerator / denominator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DualCertificate<T = i32> {
+    pub numerator: T,
+    pub denominator: T,
+}
+
+impl<T: Number> DualCertificate<T> {
+    // Return the bound this lambda proves, rounded down for integers
+    // because then every solution has an integer value.
+    //
+    // Only the items with a ratio above lambda add value - lambda * weight,
+    // so the bound is their value plus lambda times the allowed weight
+    // they leave. For the critical item's lambda they always fit, and the
+    // bound is exact. For a smaller lambda the part they exceed the allowed
+    // weight by is rounded down, which keeps the bound an upper bound.
+    pub fn bound(&self, items: &[Item<T>], allowed_weight: T) -> T {
+        let (mut value, mut weight) = (T::zero(), T::zero());
+        for item in items {
+            let ratio =
+                T::compare_ratios(item.value, item.weight, self.numerator, self.denominator);
+            if ratio == Ordering::Greater {
+                value = value + item.value;
+                weight = weight + item.weight;
+            }
+        }
+        if weight <= allowed_weight {
+            value + (allowed_weight - weight).mul_div(self.numerator, self.denominator)
+        } else {
+            value - (weight - allowed_weight).mul_div(self.numerator, self.denominator)
+        }
+    }
+}
+
+// Return the best lambda: the ratio of the critical item,
+// or zero if all items fit together.
+pub fn lp_dual<T: Number>(items: &[Item<T>], allowed_weight: T) -> DualCertificate<T> {
+    let mut remaining_weight = allowed_weight;
+    for i in ratio_order(items) {
+        if items[i].weight > remaining_weight {
+            return DualCertificate {
+                numerator: items[i].value,
+                denominator: items[i].weight,
+            };
+        }
+        remaining_weight = remaining_weight - items[i].weight;
+    }
+    DualCertificate {
+        numerator: T::zero(),
+        denominator: T::one(),
+    }
+}
+
+// What the verifier found out about a valid solution.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Verified<T = i32> {
+    pub value: T,
+    pub weight: T,
+    // The bound the certificate proves, if the model has one.
+    pub upper_bound: Option<T>,
+    pub proven_optimal: bool,
+}
+
+// Verify a claimed 0/1 knapsack solution and check it against the LP dual.
+pub fn verify<T: Number>(
+    instance: &[Item<T>],
+    allowed_weight: T,
+    solution: &[Item<T>],
+    claimed_value: T,
+) -> Result<Verified<T>, Violation<T>> {
+    let mut verified = verify_with(instance, allowed_weight, solution, claimed_value, |items| {
+        sum_values(items, false)
+    })?;
+    let bound = lp_dual(instance, allowed_weight).bound(instance, allowed_weight);
+    if verified.value > bound {
+        return Err(Violation::AboveBound {
+            value: verified.value,
+            bound,
+        });
+    }
+    verified.upper_bound = Some(bound);
+    verified.proven_optimal = verified.value == bound;
+    Ok(verified)
+}
+
+// Verify a claimed subset sum solution. In this model
+// every item's value must equal its weight.
+pub fn verify_subset_sum<T: Number>(
+    instance: &[Item<T>],
+    allowed_weight: T,
+    solution: &[Item<T>],
+    claimed_sum: T,
+) -> Result<Verified<T>, Violation<T>> {
+    if let Some(i) = instance.iter().position(|item| item.value != item.weight) {
+        return Err(Violation::Model(format!(
+            "item {} has value {} but weight {}",
+            i, instance[i].value, instance[i].weight
+        )));
+    }
+    verify(instance, allowed_weight, solution, claimed_sum)
+}
+
+// Verify a claimed solution of a model whose value isn't just the sum
+// of the selected values. value_of returns the value of a selection.
+pub fn verify_with<T: Number>(
+    instance: &[Item<T>],
+    allowed_weight: T,
+    solution: &[Item<T>],
+    claimed_value: T,
+    value_of: impl Fn(&[Item<T>]) -> T,
+) -> Result<Verified<T>, Violation<T>> {
+    if solution.len() != instance.len() {
+        return Err(Violation::WrongLength {
+            expected: instance.len(),
+            found: solution.len(),
+        });
+    }
+    let changed = instance
+        .iter()
+        .zip(solution)
+        .position(|(a, b)| a.value != b.value || a.weight != b.weight);
+    if let Some(i) = changed {
+        return Err(Violation::ItemChanged(i));
+    }
+
+    let weight = sum_weights(solution, false);
+    if weight > allowed_weight {
+        return Err(Violation::Overweight {
+            weight,
+            allowed_weight,
+        });
+    }
+    let value = value_of(solution);
+    if value != claimed_value {
+        return Err(Violation::ValueMismatch {
+            claimed: claimed_value,
+            actual: value,
+        });
+    }
+    Ok(Verified {
+        value,
+        weight,
+        upper_bound: None,
+        proven_optimal: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dp::dynamic_programming;
+    use crate::greedy::lp_bound;
+    use crate::item::{copy_items, make_item, make_items};
+    use utils::Prng;
+
+    #[test]
+    fn dual_bound_is_the_lp_bound() {
+        for seed in 0..50 {
+            let mut prng = Prng::new_with_seed(seed);
+            let items = make_items(&mut prng, 20, 1, 10, 4, 10);
+            for allowed_weight in [0, 10, 50, 200] {
+                let bound = lp_dual(&items, allowed_weight).bound(&items, allowed_weight);
+                let lp = lp_bound(&items, allowed_weight);
+                assert_eq!(bound, (lp + 1e-9).floor() as i32, "seed {seed}");
+
+                let (solution, value, _) =
+                    dynamic_programming(&mut copy_items(&items), allowed_weight);
+                let verified = verify(&items, allowed_weight, &solution, value).unwrap();
+                assert!(verified.value <= bound);
+            }
+        }
+    }
+
+    #[test]
+    fn tight_bound_proves_optimality() {
+        let mut items = vec![make_item(6, 3), make_item(4, 2), make_item(1, 5)];
+        items[0].is_selected = true;
+        items[1].is_selected = true;
+        let verified = verify(&items, 5, &items, 10).unwrap();
+        assert_eq!(verified.weight, 5);
+        assert_eq!(verified.upper_bound, Some(10));
+        assert!(verified.proven_optimal);
+    }
+
+    #[test]
+    fn violations_are_caught() {
+        let instance = vec![make_item(6, 3), make_item(4, 2)];
+        let mut both = copy_items(&instance);
+        both.iter_mut().for_each(|item| item.is_selected = true);
+
+        assert_eq!(
+            verify(&instance, 4, &both, 10),
+            Err(Violation::Overweight {
+                weight: 5,
+                allowed_weight: 4
+            })
+        );
+        assert_eq!(
+            verify(&instance, 5, &both, 11),
+            Err(Violation::ValueMismatch {
+                claimed: 11,
+                actual: 10
+            })
+        );
+        assert_eq!(
+            verify(&instance, 5, &both[..1], 6),
+            Err(Violation::WrongLength {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert!(matches!(
+            verify_subset_sum(&instance, 5, &both, 10),
+            Err(Violation::Model(_))
+        ));
+        let mut changed = copy_items(&both);
+        changed[1].weight = 1;
+        assert_eq!(
+            verify(&instance, 5, &changed, 10),
+            Err(Violation::ItemChanged(1))
+        );
+    }
+}
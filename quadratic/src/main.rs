@@ -0,0 +1,705 @@
+// 4. Quadratic Knapsack
+use knapsack::item::{copy_items, make_items, print_selected, sum_weights, Item, Solution};
+use knapsack::limits::{Budget, Limits, SearchResult};
+use knapsack::verify::{verify_with, Verified, Violation};
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+use utils::{Generator, Prng};
+
+const NUM_ITEMS: i32 = 20;
+
+// Stop a search after this long and report the best solution so far.
+const TIME_LIMIT: Duration = Duration::from_secs(10);
+
+const MIN_VALUE: i32 = 1;
+const MAX_VALUE: i32 = 10;
+const MIN_WEIGHT: i32 = 4;
+const MAX_WEIGHT: i32 = 10;
+// The upper bound of the searches needs non-negative pair profits.
+const MIN_PAIR_PROFIT: i32 = 0;
+const MAX_PAIR_PROFIT: i32 = 5;
+
+// The pairwise profits of the items. The matrix is symmetric and
+// pair_profits[i][j] is earned if both item i and item j are selected.
+type PairProfits = Vec<Vec<i32>>;
+
+// A search that stops when it hits one of the limits.
+type LimitedAlgorithm = dyn Fn(&mut [Item], &PairProfits, i32, &Limits) -> SearchResult<Item>;
+
+// Make a random symmetric matrix of pairwise profits.
+// The diagonal is zero because an item's own profit is its value.
+fn make_pair_profits(
+    prng: &mut dyn Generator,
+    num_items: i32,
+    min_profit: i32,
+    max_profit: i32,
+) -> PairProfits {
+    let n = num_items as usize;
+    let mut pair_profits: PairProfits = Vec::with_capacity(n);
+    for i in 0..n {
+        // Mirror the profits drawn for the earlier rows.
+        let row = (0..n)
+            .map(|j| match j.cmp(&i) {
+                Ordering::Less => pair_profits[j][i],
+                Ordering::Equal => 0,
+                Ordering::Greater => prng.next_i32(min_profit, max_profit),
+            })
+            .collect();
+        pair_profits.push(row);
+    }
+    pair_profits
+}
+
+// Return the total value of the items including all pairwise profits.
+// If add_all is true, add up all items.
+// If add_all is false, only add up the selected items.
+fn sum_values(items: &[Item], pair_profits: &PairProfits, add_all: bool) -> i32 {
+    let mut total = 0;
+    for i in 0..items.len() {
+        if !add_all && !items[i].is_selected {
+            continue;
+        }
+        total += items[i].value;
+        for j in (i + 1)..items.len() {
+            if add_all || items[j].is_selected {
+                total += pair_profits[i][j];
+            }
+        }
+    }
+    total
+}
+
+// Return the value of this solution.
+// If the solution is too heavy, return -1 so we prefer an empty solution.
+fn solution_value(items: &[Item], pair_profits: &PairProfits, allowed_weight: i32) -> i32 {
+    if sum_weights(items, false) > allowed_weight {
+        return -1;
+    }
+    sum_values(items, pair_profits, false)
+}
+
+// Check a claimed solution with the knapsack verifier. The value is
+// recomputed from the selected indices, independent of sum_values.
+// The model also needs a symmetric profit matrix with a zero diagonal.
+fn verify(
+    instance: &[Item],
+    pair_profits: &PairProfits,
+    allowed_weight: i32,
+    solution: &[Item],
+    claimed_value: i32,
+) -> Result<Verified, Violation> {
+    for i in 0..instance.len() {
+        if pair_profits.len() != instance.len() || pair_profits[i].len() != instance.len() {
+            return Err(Violation::Model(
+                "pair profits don't match the items".to_string(),
+            ));
+        }
+        if pair_profits[i][i] != 0 {
+            return Err(Violation::Model(format!(
+                "item {} has a pair profit with itself",
+                i
+            )));
+        }
+        if let Some(j) = (0..i).find(|&j| pair_profits[i][j] != pair_profits[j][i]) {
+            return Err(Violation::Model(format!(
+                "pair profits of items {} and {} differ",
+                i, j
+            )));
+        }
+    }
+
+    let value_of = |selection: &[Item]| {
+        let selected: Vec<usize> = (0..selection.len())
+            .filter(|&i| selection[i].is_selected)
+            .collect();
+        let mut value = 0;
+        for (k, &i) in selected.iter().enumerate() {
+            value += selection[i].value;
+            for &j in &selected[k + 1..] {
+                value += pair_profits[i][j];
+            }
+        }
+        value
+    };
+    verify_with(instance, allowed_weight, solution, claimed_value, value_of)
+}
+
+// Print what the verifier says about a solution.
+fn print_verification(verification: &Result<Verified, Violation>) {
+    match verification {
+        Ok(_) => println!("Verified"),
+        Err(violation) => println!("INVALID SOLUTION: {}", violation),
+    }
+}
+
+// Return the value gained by adding item index to the current selection.
+fn gain(items: &[Item], pair_profits: &PairProfits, index: usize) -> i32 {
+    let mut result = items[index].value;
+    for (j, item) in items.iter().enumerate() {
+        if j != index && item.is_selected {
+            result += pair_profits[index][j];
+        }
+    }
+    result
+}
+
+// Run the algorithm. Display the elapsed time and solution.
+fn run_algorithm(
+    alg: &dyn Fn(&mut [Item], &PairProfits, i32) -> Solution,
+    items: &[Item],
+    pair_profits: &PairProfits,
+    allowed_weight: i32,
+) {
+    // Copy the items so the run isn't influenced by a previous run.
+    let mut test_items = copy_items(items);
+
+    let start = Instant::now();
+
+    // Run the algorithm.
+    let (solution, total_value, function_calls) =
+        alg(&mut test_items, pair_profits, allowed_weight);
+
+    let duration = start.elapsed();
+    println!("Elapsed: {:?}", duration);
+
+    print_selected(&solution);
+    println!(
+        "Value: {}, Weight: {}, Calls: {}",
+        total_value,
+        sum_weights(&solution, false),
+        function_calls
+    );
+    print_verification(&verify(
+        items,
+        pair_profits,
+        allowed_weight,
+        &solution,
+        total_value,
+    ));
+    println!();
+}
+
+// Run a search with the given limits. Display the elapsed time,
+// the solution and, if a limit stopped the search, how far from
+// optimal the solution may be.
+fn run_limited(
+    alg: &LimitedAlgorithm,
+    items: &[Item],
+    pair_profits: &PairProfits,
+    allowed_weight: i32,
+    limits: &Limits,
+) {
+    let mut test_items = copy_items(items);
+
+    let start = Instant::now();
+    let result = alg(&mut test_items, pair_profits, allowed_weight, limits);
+    let duration = start.elapsed();
+    println!("Elapsed: {:?}", duration);
+
+    print_selected(&result.items);
+    println!(
+        "Value: {}, Weight: {}, Calls: {}",
+        result.value,
+        sum_weights(&result.items, false),
+        result.calls
+    );
+    print_verification(&verify(
+        items,
+        pair_profits,
+        allowed_weight,
+        &result.items,
+        result.value,
+    ));
+    if !result.proven_optimal {
+        println!(
+            "Not proven optimal. Upper bound: {}, Gap: {:.2}%",
+            result.upper_bound,
+            100.0 * result.gap()
+        );
+    }
+    println!();
+}
+
+// Panic if a pair profit is negative, because upper_bound
+// would then no longer be an upper bound.
+fn check_pair_profits(pair_profits: &PairProfits) {
+    assert!(
+        pair_profits.iter().flatten().all(|&profit| profit >= 0),
+        "The upper bound needs non-negative pair profits"
+    );
+}
+
+// Recursively assign values in or out of the solution.
+// Stop when a limit is hit and return the best solution found until then.
+// This is the reference solver for small numbers of items.
+fn exhaustive_search_limited(
+    items: &mut [Item],
+    pair_profits: &PairProfits,
+    allowed_weight: i32,
+    limits: &Limits,
+) -> SearchResult<Item> {
+    check_pair_profits(pair_profits);
+    let mut budget = Budget::new(limits);
+    let (solution, value) =
+        do_exhaustive_search(items, pair_profits, allowed_weight, 0, &mut budget);
+    budget.finish(solution, value)
+}
+
+fn do_exhaustive_search(
+    items: &mut [Item],
+    pair_profits: &PairProfits,
+    allowed_weight: i32,
+    next_index: usize,
+    budget: &mut Budget,
+) -> (Vec<Item>, i32) {
+    let bound = || {
+        let remaining_weight = allowed_weight - sum_weights(items, false);
+        let bound = upper_bound(items, pair_profits, next_index, remaining_weight);
+        sum_values(items, pair_profits, false) + bound.ceil() as i32
+    };
+    if !budget.visit(next_index, bound) {
+        // Leave the undecided items out.
+        return (
+            copy_items(items),
+            solution_value(items, pair_profits, allowed_weight),
+        );
+    }
+
+    if next_index >= items.len() {
+        let value = solution_value(items, pair_profits, allowed_weight);
+        budget.found(value);
+        return (copy_items(items), value);
+    }
+
+    // Skip next item:
+    items[next_index].is_selected = false;
+    let (solution_1, value_1) =
+        do_exhaustive_search(items, pair_profits, allowed_weight, next_index + 1, budget);
+
+    // Select next item:
+    items[next_index].is_selected = true;
+    let (solution_2, value_2) =
+        do_exhaustive_search(items, pair_profits, allowed_weight, next_index + 1, budget);
+    items[next_index].is_selected = false;
+
+    // Return the best solution.
+    if value_1 >= value_2 {
+        (solution_1, value_1)
+    } else {
+        (solution_2, value_2)
+    }
+}
+
+// Return an upper bound for the value that can still be added
+// to the current selection using the items from next_index on.
+//
+// Every remaining item j is given the profit it could earn at most:
+// its value, its profits with the selected items and half of its
+// profits with the other remaining items (each of these pairs is
+// shared by two items). The bound is then the fractional knapsack
+// over these profits, which is never smaller than the real optimum.
+//
+// This only holds if no pair profit is negative. Otherwise an item
+// earns more than its profit here when its partner is left out, and
+// the bound can be too low. The searches check this with
+// check_pair_profits.
+fn upper_bound(
+    items: &[Item],
+    pair_profits: &PairProfits,
+    next_index: usize,
+    remaining_weight: i32,
+) -> f64 {
+    let mut candidates: Vec<(f64, i32)> = Vec::new();
+    for j in next_index..items.len() {
+        if items[j].weight > remaining_weight {
+            continue;
+        }
+        let mut profit = items[j].value as f64;
+        for i in 0..next_index {
+            if items[i].is_selected {
+                profit += pair_profits[i][j] as f64;
+            }
+        }
+        for k in next_index..items.len() {
+            if k != j && items[k].weight <= remaining_weight {
+                profit += pair_profits[j][k] as f64 / 2.0;
+            }
+        }
+        candidates.push((profit, items[j].weight));
+    }
+
+    candidates.sort_by(|a, b| {
+        let ratio_a = a.0 / a.1 as f64;
+        let ratio_b = b.0 / b.1 as f64;
+        ratio_b.total_cmp(&ratio_a)
+    });
+
+    let mut bound = 0.0;
+    let mut capacity = remaining_weight;
+    for (profit, weight) in candidates {
+        if weight <= capacity {
+            bound += profit;
+            capacity -= weight;
+        } else {
+            bound += profit * capacity as f64 / weight as f64;
+            break;
+        }
+    }
+    bound
+}
+
+// Recursively assign values in or out of the solution.
+// Prune branches whose upper bound can't beat the best value so far.
+// Stop when a limit is hit and return the best solution found until then.
+fn branch_and_bound_limited(
+    items: &mut [Item],
+    pair_profits: &PairProfits,
+    allowed_weight: i32,
+    limits: &Limits,
+) -> SearchResult<Item> {
+    check_pair_profits(pair_profits);
+    for item in items.iter_mut() {
+        item.is_selected = false;
+    }
+    let mut best = (copy_items(items), 0, 0);
+    let mut budget = Budget::new(limits);
+
+    do_branch_and_bound(
+        items,
+        pair_profits,
+        allowed_weight,
+        0,
+        0,
+        0,
+        &mut best,
+        &mut budget,
+    );
+
+    budget.finish(best.0, best.1)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn do_branch_and_bound(
+    items: &mut [Item],
+    pair_profits: &PairProfits,
+    allowed_weight: i32,
+    next_index: usize,
+    current_value: i32,
+    current_weight: i32,
+    best: &mut Solution,
+    budget: &mut Budget,
+) {
+    if current_value > best.1 {
+        *best = (copy_items(items), current_value, 0);
+        budget.found(current_value);
+    }
+
+    let remaining_weight = allowed_weight - current_weight;
+    let bound = match next_index < items.len() {
+        true => upper_bound(items, pair_profits, next_index, remaining_weight),
+        false => 0.0,
+    };
+    if !budget.visit(next_index, || current_value + bound.ceil() as i32) {
+        return;
+    }
+
+    if next_index >= items.len() {
+        return;
+    }
+
+    if current_value as f64 + bound <= best.1 as f64 {
+        return;
+    }
+
+    if items[next_index].weight <= remaining_weight {
+        // Select next item:
+        let added_value = gain(items, pair_profits, next_index);
+        items[next_index].is_selected = true;
+        do_branch_and_bound(
+            items,
+            pair_profits,
+            allowed_weight,
+            next_index + 1,
+            current_value + added_value,
+            current_weight + items[next_index].weight,
+            best,
+            budget,
+        );
+        items[next_index].is_selected = false;
+    }
+
+    // Don't select next item:
+    do_branch_and_bound(
+        items,
+        pair_profits,
+        allowed_weight,
+        next_index + 1,
+        current_value,
+        current_weight,
+        best,
+        budget,
+    );
+}
+
+// Build a solution greedily, then improve it with local search.
+// Return the assignment, its value and the number of moves we evaluated.
+//
+// The greedy phase repeatedly adds the item with the best gain per weight.
+// The local search phase tries adding single items and swapping a selected
+// item for an unselected one until no move improves the value.
+fn greedy_local_search(
+    items: &mut [Item],
+    pair_profits: &PairProfits,
+    allowed_weight: i32,
+) -> Solution {
+    for item in items.iter_mut() {
+        item.is_selected = false;
+    }
+    let mut current_weight = 0;
+    let mut moves = 0;
+
+    // Greedy phase.
+    loop {
+        let mut best_index: Option<usize> = None;
+        let mut best_ratio = 0.0;
+        for i in 0..items.len() {
+            if items[i].is_selected || current_weight + items[i].weight > allowed_weight {
+                continue;
+            }
+            moves += 1;
+            let ratio = gain(items, pair_profits, i) as f64 / items[i].weight as f64;
+            if best_index.is_none() || ratio > best_ratio {
+                best_index = Some(i);
+                best_ratio = ratio;
+            }
+        }
+        match best_index {
+            Some(i) if best_ratio > 0.0 => {
+                items[i].is_selected = true;
+                current_weight += items[i].weight;
+            }
+            _ => break,
+        }
+    }
+
+    // Local search phase.
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..items.len() {
+            if items[i].is_selected {
+                continue;
+            }
+
+            // Try to add item i.
+            moves += 1;
+            let add_gain = gain(items, pair_profits, i);
+            if current_weight + items[i].weight <= allowed_weight && add_gain > 0 {
+                items[i].is_selected = true;
+                current_weight += items[i].weight;
+                improved = true;
+                continue;
+            }
+
+            // Try to swap item i with a selected item j.
+            for j in 0..items.len() {
+                if !items[j].is_selected {
+                    continue;
+                }
+                let new_weight = current_weight - items[j].weight + items[i].weight;
+                if new_weight > allowed_weight {
+                    continue;
+                }
+                moves += 1;
+                let delta = add_gain - pair_profits[i][j] - gain(items, pair_profits, j);
+                if delta > 0 {
+                    items[j].is_selected = false;
+                    items[i].is_selected = true;
+                    current_weight = new_weight;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    let value = solution_value(items, pair_profits, allowed_weight);
+    (copy_items(items), value, moves)
+}
+
+fn main() {
+    // Prepare a Prng using the same seed each time.
+    let mut prng = Prng::new_with_seed(1337);
+    //prng.randomize();
+
+    // Make some random items.
+    let items = make_items(
+        &mut prng, NUM_ITEMS, MIN_VALUE, MAX_VALUE, MIN_WEIGHT, MAX_WEIGHT,
+    );
+    let pair_profits = make_pair_profits(&mut prng, NUM_ITEMS, MIN_PAIR_PROFIT, MAX_PAIR_PROFIT);
+    let allowed_weight = sum_weights(&items, true) / 2;
+
+    // Display basic parameters.
+    println!("*** Parameters ***");
+    println!("# items:        {}", NUM_ITEMS);
+    println!(
+        "Total value:    {}",
+        sum_values(&items, &pair_profits, true)
+    );
+    println!("Total weight:   {}", sum_weights(&items, true));
+    println!("Allowed weight: {}", allowed_weight);
+    println!();
+
+    let limits = Limits::none().with_time(TIME_LIMIT);
+
+    println!("*** Exhaustive Search ***");
+    run_limited(
+        &exhaustive_search_limited,
+        &items,
+        &pair_profits,
+        allowed_weight,
+        &limits,
+    );
+
+    println!("*** Branch and Bound Search ***");
+    run_limited(
+        &branch_and_bound_limited,
+        &items,
+        &pair_profits,
+        allowed_weight,
+        &limits,
+    );
+
+    println!("*** Greedy + Local Search ***");
+    run_algorithm(&greedy_local_search, &items, &pair_profits, allowed_weight);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run the searches without limits.
+    fn exhaustive_search(
+        items: &mut [Item],
+        pair_profits: &PairProfits,
+        allowed_weight: i32,
+    ) -> Solution {
+        let result =
+            exhaustive_search_limited(items, pair_profits, allowed_weight, &Limits::none());
+        (result.items, result.value, result.calls)
+    }
+
+    fn branch_and_bound(
+        items: &mut [Item],
+        pair_profits: &PairProfits,
+        allowed_weight: i32,
+    ) -> Solution {
+        let result = branch_and_bound_limited(items, pair_profits, allowed_weight, &Limits::none());
+        (result.items, result.value, result.calls)
+    }
+
+    fn make_instance(seed: u32, num_items: i32) -> (Vec<Item>, PairProfits, i32) {
+        let mut prng = Prng::new_with_seed(seed);
+        let items = make_items(
+            &mut prng, num_items, MIN_VALUE, MAX_VALUE, MIN_WEIGHT, MAX_WEIGHT,
+        );
+        let pair_profits =
+            make_pair_profits(&mut prng, num_items, MIN_PAIR_PROFIT, MAX_PAIR_PROFIT);
+        let allowed_weight = sum_weights(&items, true) / 2;
+        (items, pair_profits, allowed_weight)
+    }
+
+    #[test]
+    fn pair_profits_are_symmetric() {
+        let (_, pair_profits, _) = make_instance(7, 12);
+        for (i, row) in pair_profits.iter().enumerate() {
+            assert_eq!(row[i], 0);
+            for (j, profit) in row.iter().enumerate() {
+                assert_eq!(*profit, pair_profits[j][i]);
+            }
+        }
+    }
+
+    #[test]
+    fn verifier_checks_the_profit_matrix() {
+        let (items, mut pair_profits, allowed_weight) = make_instance(7, 5);
+        let empty = copy_items(&items);
+        assert!(verify(&items, &pair_profits, allowed_weight, &empty, 0).is_ok());
+
+        pair_profits[1][3] += 1;
+        assert!(matches!(
+            verify(&items, &pair_profits, allowed_weight, &empty, 0),
+            Err(Violation::Model(_))
+        ));
+    }
+
+    #[test]
+    fn branch_and_bound_matches_exhaustive_search() {
+        for seed in 0..50 {
+            let (items, pair_profits, allowed_weight) = make_instance(seed, 12);
+
+            let (_, expected, _) =
+                exhaustive_search(&mut copy_items(&items), &pair_profits, allowed_weight);
+            let (solution, value, _) =
+                branch_and_bound(&mut copy_items(&items), &pair_profits, allowed_weight);
+
+            assert_eq!(value, expected, "seed {seed}");
+            verify(&items, &pair_profits, allowed_weight, &solution, value).unwrap();
+        }
+    }
+
+    #[test]
+    fn limited_searches_bound_the_optimum() {
+        let (items, pair_profits, allowed_weight) = make_instance(3, 14);
+        let (_, optimum, _) =
+            exhaustive_search(&mut copy_items(&items), &pair_profits, allowed_weight);
+
+        let searches: [&LimitedAlgorithm; 2] =
+            [&exhaustive_search_limited, &branch_and_bound_limited];
+        for search in searches {
+            for nodes in [1, 20, 500] {
+                let limits = Limits::none().with_nodes(nodes);
+                let result = search(
+                    &mut copy_items(&items),
+                    &pair_profits,
+                    allowed_weight,
+                    &limits,
+                );
+                assert!(result.calls <= nodes);
+                verify(
+                    &items,
+                    &pair_profits,
+                    allowed_weight,
+                    &result.items,
+                    result.value,
+                )
+                .unwrap();
+                assert!(result.value <= optimum && optimum <= result.upper_bound);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "non-negative pair profits")]
+    fn negative_pair_profits_are_rejected() {
+        let (items, mut pair_profits, allowed_weight) = make_instance(7, 5);
+        pair_profits[1][3] = -1;
+        pair_profits[3][1] = -1;
+        branch_and_bound(&mut copy_items(&items), &pair_profits, allowed_weight);
+    }
+
+    #[test]
+    fn greedy_local_search_is_feasible() {
+        for seed in 0..50 {
+            let (items, pair_profits, allowed_weight) = make_instance(seed, 12);
+
+            let (_, optimum, _) =
+                exhaustive_search(&mut copy_items(&items), &pair_profits, allowed_weight);
+            let (solution, value, _) =
+                greedy_local_search(&mut copy_items(&items), &pair_profits, allowed_weight);
+
+            verify(&items, &pair_profits, allowed_weight, &solution, value).unwrap();
+            assert!(value <= optimum, "seed {seed}");
+        }
+    }
+}
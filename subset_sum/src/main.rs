@@ -0,0 +1,110 @@
+// 5. Subset Sum and Change-Making
+use knapsack::change::{count_ways, min_coins};
+use knapsack::dp::dynamic_programming;
+use knapsack::harness::{print_verification, run_algorithm, timed};
+use knapsack::item::{print_selected, sum_weights};
+use knapsack::subset_sum::{
+    is_reachable, make_subset_sum_items, subset_sum_bitset, subset_sum_dp,
+    subset_sum_meet_in_middle, MAX_MEET_IN_MIDDLE_ITEMS,
+};
+use knapsack::verify::verify_subset_sum;
+use utils::Prng;
+
+const NUM_ITEMS: i32 = 30;
+
+const MIN_WEIGHT: i32 = 100;
+const MAX_WEIGHT: i32 = 1000;
+
+const NUM_COINS: i32 = 5;
+const MIN_COIN: i32 = 2;
+const MAX_COIN: i32 = 50;
+const AMOUNT: i32 = 500;
+
+fn main() {
+    // Prepare a Prng using the same seed each time.
+    let mut prng = Prng::new_with_seed(1337);
+    //prng.randomize();
+
+    // Make some random items whose values equal their weights.
+    let items = make_subset_sum_items(&mut prng, NUM_ITEMS, MIN_WEIGHT, MAX_WEIGHT);
+    let allowed_weight = sum_weights(&items, true) / 2;
+
+    // Display basic parameters.
+    println!("*** Parameters ***");
+    println!("# items:        {}", NUM_ITEMS);
+    println!("Total weight:   {}", sum_weights(&items, true));
+    println!("Allowed weight: {}", allowed_weight);
+    println!();
+
+    println!("*** Dynamic Programming (knapsack) ***");
+    run_algorithm(&dynamic_programming, &items, allowed_weight);
+
+    println!("*** Dynamic Programming (subset sum) ***");
+    run_algorithm(&subset_sum_dp, &items, allowed_weight);
+
+    println!("*** Bitset Shifting ***");
+    run_algorithm(&subset_sum_bitset, &items, allowed_weight);
+
+    if NUM_ITEMS as usize > MAX_MEET_IN_MIDDLE_ITEMS {
+        // Only meet in the middle if num_items is small enough.
+        println!("Too many items for meet in the middle\n");
+    } else {
+        println!("*** Meet in the Middle ***");
+        run_algorithm(&subset_sum_meet_in_middle, &items, allowed_weight);
+    }
+
+    println!("*** Exact Target ***");
+    let (selection, duration) = timed(|| is_reachable(&items, allowed_weight));
+    println!("Elapsed: {:?}", duration);
+    match selection {
+        Some(solution) => {
+            print_selected(&solution);
+            println!("Target {} is reachable", allowed_weight);
+            print_verification(&verify_subset_sum(
+                &items,
+                allowed_weight,
+                &solution,
+                allowed_weight,
+            ));
+        }
+        None => println!("Target {} is not reachable", allowed_weight),
+    }
+    println!();
+
+    // Use the weights of a few random items as coin denominations.
+    let coins = make_subset_sum_items(&mut prng, NUM_COINS, MIN_COIN, MAX_COIN);
+    print!("Coins: ");
+    for coin in coins.iter() {
+        print!("{} ", coin.weight);
+    }
+    println!();
+    println!("Amount: {}", AMOUNT);
+    println!();
+
+    println!("*** Minimum Coins ***");
+    let (result, duration) = timed(|| min_coins(&coins, AMOUNT));
+    println!("Elapsed: {:?}", duration);
+    match result {
+        Ok(Some((num_coins, counts))) => {
+            for (coin, count) in coins.iter().zip(counts) {
+                if count > 0 {
+                    print!("{}x{} ", count, coin.weight);
+                }
+            }
+            println!();
+            println!("Coins: {}", num_coins);
+        }
+        Ok(None) => println!("The amount can't be paid"),
+        Err(error) => println!("Error: {}", error),
+    }
+    println!();
+
+    println!("*** Number of Ways ***");
+    let (ways, duration) = timed(|| count_ways(&coins, AMOUNT));
+    println!("Elapsed: {:?}", duration);
+    match ways {
+        Ok(ways) => println!("Ways: {}", ways),
+        Err(error) => println!("Error: {}", error),
+    }
+    println!();
+}
@@ -0,0 +1,327 @@
+// Prompts that read the user's answers.
+//
+// A Prompter reads from any BufRead and writes to any Write, so the
+// prompts can be tested without a terminal. Every prompt returns a
+// Result. until_valid asks again after answers that can't be used
+// and only gives up when the input itself fails or ends.
+
+use num::Integer;
+use std::fmt;
+use std::io::{self, BufRead, StdinLock, Stdout, Write};
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum InputError {
+    // Reading the input or writing the prompt failed.
+    Io(io::Error),
+    // The input ended before the user answered.
+    Eof,
+    // The answer isn't a valid value of the requested type.
+    Parse {
+        input: String,
+        message: String,
+    },
+    // The answer is valid but not within min..=max.
+    OutOfRange {
+        input: String,
+        min: String,
+        max: String,
+    },
+    // The answer isn't one of the offered choices.
+    InvalidChoice(String),
+}
+
+impl InputError {
+    // Return true if asking again may give a usable answer.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, InputError::Io(_) | InputError::Eof)
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Io(error) => write!(f, "input error: {}", error),
+            InputError::Eof => write!(f, "the input ended"),
+            InputError::Parse { input, message } => write!(f, "'{}': {}", input, message),
+            InputError::OutOfRange { input, min, max } => {
+                write!(f, "'{}' is not between {} and {}", input, min, max)
+            }
+            InputError::InvalidChoice(input) => write!(f, "'{}' is not one of the choices", input),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<io::Error> for InputError {
+    fn from(error: io::Error) -> Self {
+        InputError::Io(error)
+    }
+}
+
+pub struct Prompter<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl Prompter<StdinLock<'static>, Stdout> {
+    // Prompt on the terminal.
+    pub fn stdio() -> Self {
+        Self::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    // Return the writer, to show things between the prompts.
+    pub fn output(&mut self) -> &mut W {
+        &mut self.output
+    }
+
+    // Show the prompt and return the trimmed answer.
+    pub fn get_line(&mut self, prompt: &str) -> Result<String, InputError> {
+        write!(self.output, "{prompt}")?;
+        self.output.flush()?;
+
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err(InputError::Eof);
+        }
+        Ok(line.trim().to_string())
+    }
+
+    // Prompt for a value of any type that can be parsed.
+    pub fn get<T>(&mut self, prompt: &str) -> Result<T, InputError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let line = self.get_line(prompt)?;
+        line.parse().map_err(|error: T::Err| InputError::Parse {
+            message: error.to_string(),
+            input: line,
+        })
+    }
+
+    // Prompt for an integer.
+    pub fn get_int<T>(&mut self, prompt: &str) -> Result<T, InputError>
+    where
+        T: Integer + FromStr,
+        T::Err: fmt::Display,
+    {
+        self.get(prompt)
+    }
+
+    // Prompt for a finite floating point number.
+    pub fn get_float(&mut self, prompt: &str) -> Result<f64, InputError> {
+        let line = self.get_line(prompt)?;
+        match line.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(value),
+            Ok(_) => Err(InputError::Parse {
+                input: line,
+                message: "not a finite number".to_string(),
+            }),
+            Err(error) => Err(InputError::Parse {
+                message: error.to_string(),
+                input: line,
+            }),
+        }
+    }
+
+    // Prompt for a value in min..=max.
+    pub fn get_in_range<T>(&mut self, prompt: &str, min: T, max: T) -> Result<T, InputError>
+    where
+        T: FromStr + PartialOrd + fmt::Display,
+        T::Err: fmt::Display,
+    {
+        let value: T = self.get(prompt)?;
+        check_range(value, min, max)
+    }
+
+    // Prompt for a value in min..=max. An empty answer gives the default.
+    pub fn get_in_range_or<T>(
+        &mut self,
+        prompt: &str,
+        min: T,
+        max: T,
+        default: T,
+    ) -> Result<T, InputError>
+    where
+        T: FromStr + PartialOrd + fmt::Display,
+        T::Err: fmt::Display,
+    {
+        let line = self.get_line(prompt)?;
+        if line.is_empty() {
+            return Ok(default);
+        }
+        let value = line.parse().map_err(|error: T::Err| InputError::Parse {
+            message: error.to_string(),
+            input: line,
+        })?;
+        check_range(value, min, max)
+    }
+
+    // Prompt for yes or no. An empty answer gives the default, if any.
+    pub fn get_yes_no(&mut self, prompt: &str, default: Option<bool>) -> Result<bool, InputError> {
+        let line = self.get_line(prompt)?;
+        match line.to_lowercase().as_str() {
+            "y" | "yes" => Ok(true),
+            "n" | "no" => Ok(false),
+            "" => default.ok_or(InputError::InvalidChoice(line)),
+            _ => Err(InputError::InvalidChoice(line)),
+        }
+    }
+
+    // Show a numbered menu of the choices and return the index of the
+    // one the user picks, either by its number or by its name.
+    pub fn choose(&mut self, prompt: &str, choices: &[&str]) -> Result<usize, InputError> {
+        for (i, choice) in choices.iter().enumerate() {
+            writeln!(self.output, "{:>3}) {}", i + 1, choice)?;
+        }
+        let line = self.get_line(prompt)?;
+        if let Ok(number) = line.parse::<usize>() {
+            if (1..=choices.len()).contains(&number) {
+                return Ok(number - 1);
+            }
+        }
+        choices
+            .iter()
+            .position(|choice| choice.eq_ignore_ascii_case(&line))
+            .ok_or(InputError::InvalidChoice(line))
+    }
+
+    // Ask until ask returns a usable answer. Tell the user what was
+    // wrong with each unusable one. Input errors are returned.
+    pub fn until_valid<T>(
+        &mut self,
+        mut ask: impl FnMut(&mut Self) -> Result<T, InputError>,
+    ) -> Result<T, InputError> {
+        loop {
+            match ask(self) {
+                Err(error) if error.is_retryable() => {
+                    writeln!(self.output, "Invalid input {}. Please try again.", error)?;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+// NaN compares false with everything, so it is out of every range.
+fn check_range<T: PartialOrd + fmt::Display>(value: T, min: T, max: T) -> Result<T, InputError> {
+    if !(min <= value && value <= max) {
+        return Err(InputError::OutOfRange {
+            input: value.to_string(),
+            min: min.to_string(),
+            max: max.to_string(),
+        });
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn make_prompter(input: &str) -> Prompter<Cursor<&[u8]>, Vec<u8>> {
+        Prompter::new(Cursor::new(input.as_bytes()), Vec::new())
+    }
+
+    fn output(prompter: &Prompter<Cursor<&[u8]>, Vec<u8>>) -> String {
+        String::from_utf8(prompter.output.clone()).unwrap()
+    }
+
+    #[test]
+    fn parses_numbers() {
+        let mut prompter = make_prompter(" 42 \n-7\n2.5\nabc\n");
+        assert_eq!(prompter.get_int::<u32>("A: ").unwrap(), 42);
+        assert_eq!(prompter.get_int::<i64>("B: ").unwrap(), -7);
+        assert_eq!(prompter.get_float("C: ").unwrap(), 2.5);
+        assert!(matches!(
+            prompter.get_int::<i32>("D: "),
+            Err(InputError::Parse { input, .. }) if input == "abc"
+        ));
+        assert!(matches!(
+            prompter.get_int::<i32>("E: "),
+            Err(InputError::Eof)
+        ));
+        assert_eq!(output(&prompter), "A: B: C: D: E: ");
+    }
+
+    #[test]
+    fn rejects_infinite_floats() {
+        let mut prompter = make_prompter("inf\n");
+        assert!(matches!(
+            prompter.get_float(""),
+            Err(InputError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn checks_ranges() {
+        let mut prompter = make_prompter("0\n10\n5\n");
+        assert!(matches!(
+            prompter.get_in_range("", 1, 9),
+            Err(InputError::OutOfRange { .. })
+        ));
+        assert!(prompter.get_in_range("", 1, 9).is_err());
+        assert_eq!(prompter.get_in_range("", 1, 9).unwrap(), 5);
+
+        let mut prompter = make_prompter("\n12\n3\n");
+        assert_eq!(prompter.get_in_range_or("", 1, 9, 4).unwrap(), 4);
+        assert!(prompter.get_in_range_or("", 1, 9, 4).is_err());
+        assert_eq!(prompter.get_in_range_or("", 1, 9, 4).unwrap(), 3);
+
+        let mut prompter = make_prompter("NaN\n0.5\n");
+        assert!(matches!(
+            prompter.get_in_range("", 0.0, 1.0),
+            Err(InputError::OutOfRange { .. })
+        ));
+        assert_eq!(prompter.get_in_range("", 0.0, 1.0).unwrap(), 0.5);
+    }
+
+    #[test]
+    fn answers_yes_or_no() {
+        let mut prompter = make_prompter("Y\nno\n\n\nmaybe\n");
+        assert!(prompter.get_yes_no("", None).unwrap());
+        assert!(!prompter.get_yes_no("", None).unwrap());
+        assert!(prompter.get_yes_no("", Some(true)).unwrap());
+        assert!(matches!(
+            prompter.get_yes_no("", None),
+            Err(InputError::InvalidChoice(_))
+        ));
+        assert!(prompter.get_yes_no("", Some(false)).is_err());
+    }
+
+    #[test]
+    fn chooses_by_number_or_name() {
+        let choices = ["greedy", "branch-and-bound"];
+        let mut prompter = make_prompter("2\nGREEDY\n3\n");
+        assert_eq!(prompter.choose("> ", &choices).unwrap(), 1);
+        assert_eq!(prompter.choose("> ", &choices).unwrap(), 0);
+        assert!(prompter.choose("> ", &choices).is_err());
+        assert!(output(&prompter).starts_with("  1) greedy\n  2) branch-and-bound\n> "));
+    }
+
+    #[test]
+    fn retries_until_valid() {
+        let mut prompter = make_prompter("x\n100\n7\n");
+        let value = prompter
+            .until_valid(|p| p.get_in_range("Count: ", 1, 10))
+            .unwrap();
+        assert_eq!(value, 7);
+        assert_eq!(output(&prompter).matches("Please try again").count(), 2);
+
+        // The end of the input stops the retries.
+        let mut prompter = make_prompter("x\n");
+        assert!(matches!(
+            prompter.until_valid(|p| p.get_int::<i32>("")),
+            Err(InputError::Eof)
+        ));
+    }
+}
@@ -1,82 +1,20 @@
-use num::Integer;
-use std::fmt::Debug;
-use std::io;
-use std::io::Write;
-use std::str::FromStr;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-// Prompt the user for an i32.
-pub fn get_i32(prompt: &str) -> i32 {
-    get_int(prompt).expect("Error parsing integer")
+mod input;
+mod random;
+
+pub use input::{InputError, Prompter};
+pub use random::{
+    seed_from_time, Generator, GeneratorKind, Pcg32, Prng, SampleRange, Sampling, SplitMix64,
+    XorShift64, Xoshiro256,
+};
+
+// Prompt the user for an i32 until the answer is valid.
+pub fn get_i32(prompt: &str) -> Result<i32, InputError> {
+    Prompter::stdio().until_valid(|p| p.get_int(prompt))
 }
 
-// Prompt the user for an i64.
-pub fn get_i64(prompt: &str) -> i64 {
-    get_int(prompt).expect("Error parsing integer")
-}
-
-// Prompt the user for an integer
-fn get_int<T: Integer + FromStr + Debug>(prompt: &str) -> Result<T, <T as FromStr>::Err> {
-    print!("{prompt}");
-    io::stdout().flush().unwrap();
-
-    let mut str_value = String::new();
-    io::stdin()
-        .read_line(&mut str_value)
-        .expect("Error reading input");
-
-    str_value.trim().parse::<T>()
-}
-
-pub struct Prng {
-    seed: u32,
-}
-
-impl Prng {
-    pub fn new() -> Self {
-        let mut prng = Self { seed: 0 };
-        prng.randomize();
-        return prng;
-    }
-
-    pub fn new_with_seed(seed: u32) -> Self {
-        Self { seed }
-    }
-
-    pub fn randomize(&mut self) {
-        let millis = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_millis();
-        self.seed = millis as u32;
-    }
-
-    // Return a pseudorandom value in the range [0, 2147483647].
-    pub fn next_u32(&mut self) -> u32 {
-        self.seed = self.seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
-        self.seed %= 1 << 31;
-        return self.seed;
-    }
-
-    // Return a pseudorandom value in the range [0.0, 1.0).
-    pub fn next_f64(&mut self) -> f64 {
-        let f = self.next_u32() as f64;
-        return f / (2147483647.0 + 1.0);
-    }
-
-    // Return a pseudorandom value in the range [min, max).
-    pub fn next_i32(&mut self, min: i32, max: i32) -> i32 {
-        let range = (max - min) as f64;
-        let result = min as f64 + range * self.next_f64();
-        return result as i32;
-    }
-
-    // Return a pseudorandom value in the range [min, max).
-    pub fn next_i64(&mut self, min: i64, max: i64) -> i64 {
-        let range = (max - min) as f64;
-        let result = min as f64 + range * self.next_f64();
-        return result as i64;
-    }
+// Prompt the user for an i64 until the answer is valid.
+pub fn get_i64(prompt: &str) -> Result<i64, InputError> {
+    Prompter::stdio().until_valid(|p| p.get_int(prompt))
 }
 
 // Make a vector of random i32 values in the range [0 and max).
@@ -92,37 +30,146 @@ pub fn make_random_vec(num_items: i32, max: i32) -> Vec<i32> {
 }
 
 // Create string of first num_items items
-pub fn make_vec_string<T: std::fmt::Display>(vec: &Vec<T>, num_items: i32) -> String {
+pub fn make_vec_string<T: std::fmt::Display>(vec: &[T], num_items: i32) -> String {
     let mut max = vec.len();
     if max > num_items as usize {
         max = num_items as usize;
     }
 
     let mut string = String::new();
-    string.push_str("[");
+    string.push('[');
 
     if max > 0usize {
         string.push_str(&vec[0].to_string());
     }
 
-    for i in 1usize..max {
-        string.push_str(" ");
-        string.push_str(&vec[i].to_string());
+    for item in vec.iter().take(max).skip(1) {
+        string.push(' ');
+        string.push_str(&item.to_string());
     }
-    string.push_str("]");
+    string.push(']');
     string
 }
 
 // Print at most num_items items.
-pub fn print_vec<T: std::fmt::Display>(vec: &Vec<T>, num_items: i32) {
+pub fn print_vec<T: std::fmt::Display>(vec: &[T], num_items: i32) {
     println!("{}", make_vec_string(vec, num_items));
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    // Return the chi-square statistic of the bucket counts
+    // against equally likely buckets.
+    fn chi_square(counts: &[u64]) -> f64 {
+        let total: u64 = counts.iter().sum();
+        let expected = total as f64 / counts.len() as f64;
+        counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
 
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn small_ranges_are_uniform() {
+        // The 0.1% critical value for 9 degrees of freedom.
+        const CRITICAL: f64 = 27.88;
+        for kind in GeneratorKind::all() {
+            let mut generator = kind.make(1337);
+            let mut counts = [0u64; 10];
+            for _ in 0..100_000 {
+                counts[generator.gen_range(0..10usize)] += 1;
+            }
+            let statistic = chi_square(&counts);
+            assert!(statistic < CRITICAL, "{kind}: {statistic}");
+        }
+    }
+
+    #[test]
+    fn full_i8_range_is_uniform() {
+        // The 0.1% critical value for 255 degrees of freedom.
+        const CRITICAL: f64 = 330.52;
+        let mut generator = Xoshiro256::new_with_seed(7);
+        let mut counts = [0u64; 256];
+        for _ in 0..256 * 400 {
+            let value = generator.gen_range(i8::MIN..=i8::MAX);
+            counts[(value as i16 - i8::MIN as i16) as usize] += 1;
+        }
+        let statistic = chi_square(&counts);
+        assert!(statistic < CRITICAL, "{statistic}");
+    }
+
+    #[test]
+    fn large_u64_range_is_uniform() {
+        // This range is far wider than the 53 bits of an f64.
+        // Split it into three equal buckets by the top two bits.
+        // The 0.1% critical value for 2 degrees of freedom.
+        const CRITICAL: f64 = 13.82;
+        let mut generator = Pcg32::new_with_seed(11);
+        let mut counts = [0u64; 3];
+        for _ in 0..90_000 {
+            let value = generator.gen_range(0..3u64 << 62);
+            counts[(value >> 62) as usize] += 1;
+        }
+        let statistic = chi_square(&counts);
+        assert!(statistic < CRITICAL, "{statistic}");
+    }
+
+    #[test]
+    fn i128_range_is_uniform() {
+        // The 0.1% critical value for 4 degrees of freedom.
+        const CRITICAL: f64 = 18.47;
+        let mut generator = SplitMix64::new_with_seed(3);
+        let start = i128::MIN / 2;
+        let bucket = i128::MAX / 5;
+        let mut counts = [0u64; 5];
+        for _ in 0..50_000 {
+            let value = generator.gen_range(start..start + 5 * bucket);
+            counts[((value - start) / bucket) as usize] += 1;
+        }
+        let statistic = chi_square(&counts);
+        assert!(statistic < CRITICAL, "{statistic}");
+    }
+
+    #[test]
+    fn ranges_stay_in_bounds() {
+        let mut generator = XorShift64::new_with_seed(5);
+        for _ in 0..1000 {
+            let value = generator.gen_range(i32::MIN..i32::MAX);
+            assert!(value < i32::MAX);
+            let value = generator.next_i64(i64::MIN, i64::MAX);
+            assert!(value < i64::MAX);
+            assert_eq!(generator.gen_range(-3..=-3), -3);
+            assert!((250..=u8::MAX).contains(&generator.gen_range(250..=u8::MAX)));
+        }
+        // Full ranges need the whole width of the generator.
+        generator.gen_range(u64::MIN..=u64::MAX);
+        generator.gen_range(i128::MIN..=i128::MAX);
+    }
+
+    #[test]
+    fn trait_objects_sample_ranges() {
+        let mut generator: Box<dyn Generator> = GeneratorKind::Pcg32.make(9);
+        for _ in 0..100 {
+            assert!((-5..5).contains(&generator.gen_range(-5i16..5)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn empty_range_panics() {
+        Xoshiro256::new_with_seed(1).gen_range(5..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn reversed_range_panics() {
+        SplitMix64::new_with_seed(1).next_i32(10, 1);
+    }
 }
@@ -0,0 +1,664 @@
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// A source of pseudorandom numbers.
+//
+// Implementations only have to provide next_u64. The other methods are
+// built on it, but a generator may override them, as Prng does to keep
+// reproducing the values it always produced.
+pub trait Generator {
+    // Return the next 64 pseudorandom bits.
+    fn next_u64(&mut self) -> u64;
+
+    // Return a pseudorandom u32.
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // Return a pseudorandom value in the range [0.0, 1.0).
+    fn next_f64(&mut self) -> f64 {
+        // Use the top 53 bits, the precision of an f64.
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Return a pseudorandom value in the range [min, max).
+    // Panics if the range is empty.
+    fn next_i32(&mut self, min: i32, max: i32) -> i32 {
+        (min..max).sample(self)
+    }
+
+    // Return a pseudorandom value in the range [min, max).
+    // Panics if the range is empty.
+    fn next_i64(&mut self, min: i64, max: i64) -> i64 {
+        (min..max).sample(self)
+    }
+}
+
+// Sampling methods for every generator, also behind a reference or a Box.
+// They are generic, so they can't be part of Generator itself, which is
+// used as a trait object.
+pub trait Sampling: Generator {
+    // Return an unbiased value from a Range or RangeInclusive of any integer type.
+    // Panics if the range is empty.
+    fn gen_range<T, R: SampleRange<T>>(&mut self, range: R) -> T {
+        range.sample(self)
+    }
+
+    // Return a uniformly distributed value in the range [min, max).
+    fn gen_uniform(&mut self, min: f64, max: f64) -> f64 {
+        assert!(min < max, "cannot sample from empty range {:?}", min..max);
+        min + (max - min) * self.next_f64()
+    }
+
+    // Return a normally distributed value (Box-Muller transform).
+    fn gen_normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        assert!(std_dev >= 0.0, "standard deviation must not be negative");
+        // 1 - next_f64 lies in (0, 1], so the logarithm is finite.
+        let radius = (-2.0 * (1.0 - self.next_f64()).ln()).sqrt();
+        let angle = 2.0 * std::f64::consts::PI * self.next_f64();
+        mean + std_dev * radius * angle.cos()
+    }
+
+    // Return an exponentially distributed value with the given rate.
+    fn gen_exponential(&mut self, rate: f64) -> f64 {
+        assert!(rate > 0.0, "rate must be positive");
+        -(1.0 - self.next_f64()).ln() / rate
+    }
+
+    // Shuffle the values in place (Fisher-Yates).
+    fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = self.gen_range(0..=i);
+            values.swap(i, j);
+        }
+    }
+
+    // Return a random value, or None if there are no values.
+    fn choose<'a, T>(&mut self, values: &'a [T]) -> Option<&'a T> {
+        match values.len() {
+            0 => None,
+            len => Some(&values[self.gen_range(0..len)]),
+        }
+    }
+
+    // Return amount distinct indices below len in random order.
+    // Panics if amount is greater than len.
+    fn sample_indices(&mut self, len: usize, amount: usize) -> Vec<usize> {
+        assert!(amount <= len, "cannot sample {amount} of {len} indices");
+        // Only the first amount steps of a Fisher-Yates shuffle.
+        let mut indices: Vec<usize> = (0..len).collect();
+        for i in 0..amount {
+            let j = self.gen_range(i..len);
+            indices.swap(i, j);
+        }
+        indices.truncate(amount);
+        indices
+    }
+
+    // Return amount of the values, without replacement.
+    fn choose_multiple<T: Clone>(&mut self, values: &[T], amount: usize) -> Vec<T> {
+        self.sample_indices(values.len(), amount)
+            .into_iter()
+            .map(|i| values[i].clone())
+            .collect()
+    }
+
+    // Return an index with probability proportional to its weight,
+    // or None if no weight is positive.
+    // Panics if a weight is negative or not finite.
+    fn choose_weighted(&mut self, weights: &[f64]) -> Option<usize> {
+        assert!(
+            weights.iter().all(|w| w.is_finite() && *w >= 0.0),
+            "weights must be finite and not negative"
+        );
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = total * self.next_f64();
+        for (i, &weight) in weights.iter().enumerate() {
+            if target < weight {
+                return Some(i);
+            }
+            target -= weight;
+        }
+        // Rounding can leave target just above the last weight.
+        weights.iter().rposition(|&weight| weight > 0.0)
+    }
+}
+
+impl<G: Generator + ?Sized> Sampling for G {}
+
+// A range of integers that a generator can pick a value from.
+pub trait SampleRange<T> {
+    fn sample<G: Generator + ?Sized>(self, generator: &mut G) -> T;
+}
+
+// Return an unbiased value in [0, span) with Lemire's method:
+// the high half of next_u64 * span is the result, and the rare
+// products whose low half falls below 2^64 mod span are rejected.
+// A span of zero stands for the full 2^64 values.
+fn below_u64<G: Generator + ?Sized>(generator: &mut G, span: u64) -> u64 {
+    if span == 0 {
+        return generator.next_u64();
+    }
+    let mut product = generator.next_u64() as u128 * span as u128;
+    if (product as u64) < span {
+        let threshold = span.wrapping_neg() % span;
+        while (product as u64) < threshold {
+            product = generator.next_u64() as u128 * span as u128;
+        }
+    }
+    (product >> 64) as u64
+}
+
+// Return an unbiased value in [0, span) by masking and rejecting.
+// A span of zero stands for the full 2^128 values.
+fn below_u128<G: Generator + ?Sized>(generator: &mut G, span: u128) -> u128 {
+    let mask = match span {
+        0 => u128::MAX,
+        _ => u128::MAX
+            .checked_shr((span - 1).leading_zeros())
+            .unwrap_or(0),
+    };
+    loop {
+        let value = ((generator.next_u64() as u128) << 64 | generator.next_u64() as u128) & mask;
+        if span == 0 || value < span {
+            return value;
+        }
+    }
+}
+
+// Implement SampleRange for integer types. The span is computed in the
+// unsigned type of the same width, so ranges like i32::MIN..i32::MAX
+// don't overflow, and the offset wraps back into the signed type.
+macro_rules! impl_sample_range {
+    ($below:ident, $wide:ty, $($t:ty => $unsigned:ty),*) => {$(
+        impl SampleRange<$t> for Range<$t> {
+            fn sample<G: Generator + ?Sized>(self, generator: &mut G) -> $t {
+                assert!(self.start < self.end, "cannot sample from empty range {:?}", self);
+                let span = self.end.wrapping_sub(self.start) as $unsigned as $wide;
+                let offset = $below(generator, span);
+                self.start.wrapping_add(offset as $unsigned as $t)
+            }
+        }
+
+        impl SampleRange<$t> for RangeInclusive<$t> {
+            fn sample<G: Generator + ?Sized>(self, generator: &mut G) -> $t {
+                let (start, end) = self.into_inner();
+                assert!(start <= end, "cannot sample from empty range {:?}", start..=end);
+                let span = (end.wrapping_sub(start) as $unsigned as $wide).wrapping_add(1);
+                let offset = $below(generator, span);
+                start.wrapping_add(offset as $unsigned as $t)
+            }
+        }
+    )*};
+}
+
+impl_sample_range!(below_u64, u64,
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+impl_sample_range!(below_u128, u128, u128 => u128, i128 => u128);
+
+// Return a 64-bit seed taken from the current time.
+pub fn seed_from_time() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_nanos();
+    nanos as u64 ^ (nanos >> 64) as u64
+}
+
+// The original 31-bit linear congruential generator.
+// Its low bits are highly correlated, but it is kept
+// so that old results can be reproduced.
+pub struct Prng {
+    seed: u32,
+}
+
+impl Default for Prng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Prng {
+    pub fn new() -> Self {
+        let mut prng = Self { seed: 0 };
+        prng.randomize();
+        prng
+    }
+
+    pub fn new_with_seed(seed: u32) -> Self {
+        Self { seed }
+    }
+
+    pub fn randomize(&mut self) {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis();
+        self.seed = millis as u32;
+    }
+}
+
+impl Generator for Prng {
+    // Combine three steps, because each step only has 31 bits.
+    fn next_u64(&mut self) -> u64 {
+        let high = self.next_u32() as u64;
+        let middle = self.next_u32() as u64;
+        let low = self.next_u32() as u64;
+        (high << 33) ^ (middle << 2) ^ low
+    }
+
+    // Return a pseudorandom value in the range [0, 2147483647].
+    fn next_u32(&mut self) -> u32 {
+        self.seed = self.seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        self.seed %= 1 << 31;
+        self.seed
+    }
+
+    // Return a pseudorandom value in the range [0.0, 1.0).
+    fn next_f64(&mut self) -> f64 {
+        let f = self.next_u32() as f64;
+        f / (2147483647.0 + 1.0)
+    }
+
+    // The old, slightly biased mapping through next_f64,
+    // so that seeded item sets stay the same. gen_range
+    // samples ranges without the bias.
+    fn next_i32(&mut self, min: i32, max: i32) -> i32 {
+        assert!(min < max, "cannot sample from empty range {:?}", min..max);
+        let range = max as f64 - min as f64;
+        (min as f64 + range * self.next_f64()) as i32
+    }
+
+    fn next_i64(&mut self, min: i64, max: i64) -> i64 {
+        assert!(min < max, "cannot sample from empty range {:?}", min..max);
+        let range = max as f64 - min as f64;
+        (min as f64 + range * self.next_f64()) as i64
+    }
+}
+
+// SplitMix64 by Sebastiano Vigna. It is fast, accepts any seed and
+// is also used to expand 64-bit seeds for the other generators.
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new() -> Self {
+        Self::new_with_seed(seed_from_time())
+    }
+
+    pub fn new_with_seed(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl Default for SplitMix64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Generator for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+// Marsaglia's xorshift64. The state must never be zero.
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    pub fn new() -> Self {
+        Self::new_with_seed(seed_from_time())
+    }
+
+    // The seed is scrambled with SplitMix64 so that similar
+    // seeds give unrelated sequences and zero is avoided.
+    pub fn new_with_seed(seed: u64) -> Self {
+        let mut state = SplitMix64::new_with_seed(seed).next_u64();
+        if state == 0 {
+            state = 0x9e37_79b9_7f4a_7c15;
+        }
+        Self { state }
+    }
+}
+
+impl Default for XorShift64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Generator for XorShift64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+// xoshiro256** by Blackman and Vigna, a good general purpose generator.
+pub struct Xoshiro256 {
+    state: [u64; 4],
+}
+
+impl Xoshiro256 {
+    pub fn new() -> Self {
+        Self::new_with_seed(seed_from_time())
+    }
+
+    // Expand the seed into the 256-bit state with SplitMix64.
+    pub fn new_with_seed(seed: u64) -> Self {
+        let mut splitmix = SplitMix64::new_with_seed(seed);
+        let mut state = [0; 4];
+        for word in state.iter_mut() {
+            *word = splitmix.next_u64();
+        }
+        Self { state }
+    }
+}
+
+impl Default for Xoshiro256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Generator for Xoshiro256 {
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+}
+
+// PCG32 (XSH RR) by Melissa O'Neill. Its native output is 32 bits.
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    pub fn new() -> Self {
+        Self::new_with_seed(seed_from_time())
+    }
+
+    pub fn new_with_seed(seed: u64) -> Self {
+        Self::new_with_stream(seed, 0xda3e_39cb_94b9_5bdb)
+    }
+
+    // Different streams give different sequences for the same seed.
+    pub fn new_with_stream(seed: u64, stream: u64) -> Self {
+        let mut pcg = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        pcg.step();
+        pcg.state = pcg.state.wrapping_add(seed);
+        pcg.step();
+        pcg
+    }
+
+    // Advance the state and return the output for the old state.
+    fn step(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(self.increment);
+        let xor_shifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xor_shifted.rotate_right(rotation)
+    }
+}
+
+impl Default for Pcg32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Generator for Pcg32 {
+    fn next_u64(&mut self) -> u64 {
+        let high = self.step() as u64;
+        let low = self.step() as u64;
+        (high << 32) | low
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.step()
+    }
+}
+
+// The generators that can be chosen by name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorKind {
+    Lcg,
+    SplitMix64,
+    XorShift64,
+    Xoshiro256,
+    Pcg32,
+}
+
+impl GeneratorKind {
+    pub fn all() -> [GeneratorKind; 5] {
+        [
+            GeneratorKind::Lcg,
+            GeneratorKind::SplitMix64,
+            GeneratorKind::XorShift64,
+            GeneratorKind::Xoshiro256,
+            GeneratorKind::Pcg32,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeneratorKind::Lcg => "lcg",
+            GeneratorKind::SplitMix64 => "splitmix64",
+            GeneratorKind::XorShift64 => "xorshift64",
+            GeneratorKind::Xoshiro256 => "xoshiro256",
+            GeneratorKind::Pcg32 => "pcg32",
+        }
+    }
+
+    // Make a generator of this kind. The LCG only uses the low 32 bits of the seed.
+    pub fn make(&self, seed: u64) -> Box<dyn Generator> {
+        match self {
+            GeneratorKind::Lcg => Box::new(Prng::new_with_seed(seed as u32)),
+            GeneratorKind::SplitMix64 => Box::new(SplitMix64::new_with_seed(seed)),
+            GeneratorKind::XorShift64 => Box::new(XorShift64::new_with_seed(seed)),
+            GeneratorKind::Xoshiro256 => Box::new(Xoshiro256::new_with_seed(seed)),
+            GeneratorKind::Pcg32 => Box::new(Pcg32::new_with_seed(seed)),
+        }
+    }
+}
+
+impl fmt::Display for GeneratorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for GeneratorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        GeneratorKind::all()
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| format!("Unknown generator: {s}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lcg_reproduces_old_values() {
+        let mut prng = Prng::new_with_seed(1337);
+        let values: Vec<i32> = (0..5).map(|_| prng.next_i32(1, 10)).collect();
+        assert_eq!(values, vec![1, 7, 4, 9, 4]);
+
+        // gen_range goes through next_u64 instead.
+        let mut prng = Prng::new_with_seed(1337);
+        let values: Vec<i32> = (0..5).map(|_| prng.gen_range(1..10)).collect();
+        assert_eq!(values, vec![1, 9, 7, 8, 9]);
+    }
+
+    #[test]
+    fn splitmix64_reference_values() {
+        let mut splitmix = SplitMix64::new_with_seed(1234567);
+        assert_eq!(splitmix.next_u64(), 6457827717110365317);
+        assert_eq!(splitmix.next_u64(), 3203168211198807973);
+        assert_eq!(splitmix.next_u64(), 9817491932198370423);
+    }
+
+    #[test]
+    fn pcg32_reference_values() {
+        let mut pcg = Pcg32::new_with_stream(42, 54);
+        assert_eq!(pcg.next_u32(), 0xa15c_02b7);
+        assert_eq!(pcg.next_u32(), 0x7b47_f409);
+        assert_eq!(pcg.next_u32(), 0xba1d_3330);
+    }
+
+    #[test]
+    fn xoshiro256_reference_values() {
+        let mut xoshiro = Xoshiro256 {
+            state: [1, 2, 3, 4],
+        };
+        assert_eq!(xoshiro.next_u64(), 11520);
+        assert_eq!(xoshiro.next_u64(), 0);
+        assert_eq!(xoshiro.next_u64(), 1509978240);
+    }
+
+    #[test]
+    fn xorshift64_reference_values() {
+        let mut xorshift = XorShift64 { state: 1 };
+        assert_eq!(xorshift.next_u64(), 1082269761);
+        assert_eq!(xorshift.next_u64(), 1152992998833853505);
+    }
+
+    #[test]
+    fn generators_are_reproducible_and_in_range() {
+        for kind in GeneratorKind::all() {
+            let mut first = kind.make(2024);
+            let mut second = kind.make(2024);
+            for _ in 0..1000 {
+                let f = first.next_f64();
+                assert!((0.0..1.0).contains(&f), "{kind}");
+                assert_eq!(f, second.next_f64(), "{kind}");
+                let i = first.next_i32(-5, 5);
+                assert!((-5..5).contains(&i), "{kind}");
+                second.next_i32(-5, 5);
+            }
+        }
+    }
+
+    #[test]
+    fn helpers_are_deterministic() {
+        fn draw(generator: &mut dyn Generator) -> String {
+            let mut values: Vec<i32> = (0..20).collect();
+            generator.shuffle(&mut values);
+            format!(
+                "{:?} {:?} {:?} {:?} {} {} {}",
+                values,
+                generator.choose(&values),
+                generator.choose_multiple(&values, 5),
+                generator.choose_weighted(&[1.0, 0.0, 3.0]),
+                generator.gen_uniform(-1.0, 1.0),
+                generator.gen_normal(10.0, 2.0),
+                generator.gen_exponential(0.5),
+            )
+        }
+
+        for kind in GeneratorKind::all() {
+            let first = draw(kind.make(42).as_mut());
+            assert_eq!(first, draw(kind.make(42).as_mut()), "{kind}");
+            assert_ne!(first, draw(kind.make(43).as_mut()), "{kind}");
+        }
+    }
+
+    #[test]
+    fn shuffle_and_sample_without_replacement() {
+        let mut generator = Xoshiro256::new_with_seed(8);
+        let mut values: Vec<usize> = (0..50).collect();
+        generator.shuffle(&mut values);
+        assert_ne!(values, (0..50).collect::<Vec<usize>>());
+        values.sort_unstable();
+        assert_eq!(values, (0..50).collect::<Vec<usize>>());
+
+        let mut indices = generator.sample_indices(50, 20);
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(indices.len(), 20);
+        assert!(indices.iter().all(|&i| i < 50));
+        assert_eq!(generator.sample_indices(5, 0), vec![]);
+        assert_eq!(generator.choose::<i32>(&[]), None);
+    }
+
+    #[test]
+    fn weighted_choice_follows_the_weights() {
+        let mut generator = Pcg32::new_with_seed(5);
+        let mut counts = [0; 3];
+        for _ in 0..40_000 {
+            counts[generator.choose_weighted(&[1.0, 0.0, 3.0]).unwrap()] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert!((29_000..31_000).contains(&counts[2]), "{counts:?}");
+        assert_eq!(generator.choose_weighted(&[0.0, 0.0]), None);
+    }
+
+    #[test]
+    fn distributions_have_the_right_moments() {
+        let mut generator = SplitMix64::new_with_seed(17);
+        let n = 100_000;
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+
+        let normal: Vec<f64> = (0..n).map(|_| generator.gen_normal(10.0, 2.0)).collect();
+        let normal_mean = mean(&normal);
+        let variance = normal
+            .iter()
+            .map(|x| (x - normal_mean).powi(2))
+            .sum::<f64>()
+            / n as f64;
+        assert!((normal_mean - 10.0).abs() < 0.05, "{normal_mean}");
+        assert!((variance.sqrt() - 2.0).abs() < 0.05, "{variance}");
+
+        let exponential: Vec<f64> = (0..n).map(|_| generator.gen_exponential(0.5)).collect();
+        assert!(exponential.iter().all(|&x| x >= 0.0));
+        assert!((mean(&exponential) - 2.0).abs() < 0.05);
+
+        let uniform: Vec<f64> = (0..n).map(|_| generator.gen_uniform(-1.0, 3.0)).collect();
+        assert!(uniform.iter().all(|x| (-1.0..3.0).contains(x)));
+        assert!((mean(&uniform) - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn generator_kinds_parse() {
+        for kind in GeneratorKind::all() {
+            assert_eq!(kind.name().parse::<GeneratorKind>(), Ok(kind));
+        }
+        assert!("mersenne".parse::<GeneratorKind>().is_err());
+    }
+}